@@ -0,0 +1,97 @@
+//! angles in degrees that always stay within a single turn
+
+use crate::physics::Point;
+use serde::{Deserialize, Serialize};
+
+/// an angle in degrees, kept within [0, 360). 0 points straight up the
+/// screen and angles grow clockwise, matching how the ship is drawn.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "f32", into = "f32")]
+pub struct Angle(f32);
+impl Angle {
+    pub fn from_degrees(degrees: f32) -> Angle {
+        Angle(degrees.rem_euclid(360.))
+    }
+
+    /// the direction from one point toward another
+    pub fn towards(from: Point, to: Point) -> Angle {
+        Angle::from_degrees((to.x - from.x).atan2(from.y - to.y).to_degrees())
+    }
+
+    pub fn degrees(&self) -> f32 {
+        self.0
+    }
+
+    pub fn radians(&self) -> f32 {
+        self.0.to_radians()
+    }
+
+    /// turns the angle by `degrees`, clockwise if positive
+    pub fn rotate(&mut self, degrees: f32) {
+        *self = Angle::from_degrees(self.0 + degrees);
+    }
+
+    /// the smallest turn from this angle to `other`, in degrees between -180
+    /// and 180. Positive means clockwise.
+    pub fn shortest_difference(&self, other: Angle) -> f32 {
+        let difference = (other.0 - self.0).rem_euclid(360.);
+        if difference > 180. {
+            difference - 360.
+        } else {
+            difference
+        }
+    }
+
+    /// the angle `amount` of the way from this one to `other`, turning the
+    /// short way round
+    pub fn lerp(&self, other: Angle, amount: f32) -> Angle {
+        Angle::from_degrees(self.0 + self.shortest_difference(other) * amount)
+    }
+}
+impl From<f32> for Angle {
+    fn from(degrees: f32) -> Angle {
+        Angle::from_degrees(degrees)
+    }
+}
+impl From<Angle> for f32 {
+    fn from(angle: Angle) -> f32 {
+        angle.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn angles_wrap_into_one_turn() {
+        assert_eq!(Angle::from_degrees(-90.).degrees(), 270.);
+        assert_eq!(Angle::from_degrees(360.).degrees(), 0.);
+        assert_eq!(Angle::from_degrees(725.).degrees(), 5.);
+
+        let mut angle = Angle::from_degrees(350.);
+        angle.rotate(20.);
+        assert_eq!(angle.degrees(), 10.);
+        angle.rotate(-30.);
+        assert_eq!(angle.degrees(), 340.);
+    }
+
+    #[test]
+    fn differences_take_the_short_way_round() {
+        let north = Angle::from_degrees(0.);
+        assert_eq!(Angle::from_degrees(350.).shortest_difference(Angle::from_degrees(10.)), 20.);
+        assert_eq!(Angle::from_degrees(10.).shortest_difference(Angle::from_degrees(350.)), -20.);
+        assert_eq!(north.shortest_difference(Angle::from_degrees(180.)), 180.);
+        assert_eq!(north.shortest_difference(north), 0.);
+    }
+
+    #[test]
+    fn lerping_crosses_zero_instead_of_going_the_long_way() {
+        let from = Angle::from_degrees(340.);
+        let to = Angle::from_degrees(20.);
+        assert_eq!(from.lerp(to, 0.), from);
+        assert_eq!(from.lerp(to, 0.5).degrees(), 0.);
+        assert_eq!(from.lerp(to, 1.), to);
+        assert_eq!(to.lerp(from, 0.25).degrees(), 10.);
+    }
+}
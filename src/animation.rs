@@ -0,0 +1,54 @@
+//! keyframed values for anything that pulses, bobs or spins
+
+use serde::{Deserialize, Serialize};
+
+/// a value that moves between keyframes over time. Anything that pulses,
+/// bobs or spins can hold one and read its value when drawing.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Animation {
+    /// (seconds, value) pairs, in time order
+    pub keyframes: Vec<(f32, f32)>,
+    pub looping: bool,
+    pub time: f32,
+}
+impl Animation {
+    pub fn new(keyframes: Vec<(f32, f32)>, looping: bool) -> Animation {
+        Animation {
+            keyframes,
+            looping,
+            time: 0.,
+        }
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.keyframes.last().map_or(0., |(time, _)| *time)
+    }
+
+    /// moves the animation forward by `delta` seconds
+    pub fn update(&mut self, delta: f32) {
+        self.time += delta;
+        let duration = self.duration();
+        if self.looping && duration > 0. {
+            self.time %= duration;
+        }
+    }
+
+    /// the current value, interpolated between the surrounding keyframes
+    pub fn value(&self) -> f32 {
+        let mut previous = match self.keyframes.first() {
+            Some(keyframe) => *keyframe,
+            None => return 0.,
+        };
+        if self.time <= previous.0 {
+            return previous.1;
+        }
+        for &(time, value) in self.keyframes.iter() {
+            if self.time < time {
+                let progress = (self.time - previous.0) / (time - previous.0);
+                return previous.1 + (value - previous.1) * progress;
+            }
+            previous = (time, value);
+        }
+        previous.1
+    }
+}
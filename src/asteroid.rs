@@ -0,0 +1,252 @@
+//! asteroids, what they're made of, and the warnings shown before they arrive
+
+use crate::angle::Angle;
+use crate::animation::Animation;
+use crate::effects::DamageFlash;
+use crate::physics::{field_size, Point, TimeScale, Velocity};
+use crate::random::{Rng, WeightedTable};
+use crate::satellite::{Satellite, SATELLITE_CHANCE};
+use crate::turret::{Turret, TURRET_CHANCE};
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub const SPAWN_WARNING_TIME: f64 = 1.;
+/// the most hits a large rock takes, once the waves are far enough along
+pub const MAX_LARGE_ASTEROID_HP: u8 = 3;
+/// waves between each extra hit a large rock takes
+const WAVES_PER_EXTRA_HP: u32 = 2;
+pub const ICE_SHELL_CHANCE: f32 = 0.25;
+pub const CORE_SCALE: f32 = 0.7;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Asteroid {
+    pub pos: Point,
+    pub vel: Velocity,
+    pub rotation: Angle,
+    /// degrees per second
+    pub rot_speed: f32,
+    pub size: f32,
+    pub sides: u8,
+    /// what the asteroid is made of, from the core outward. Only the outermost
+    /// layer can be damaged, and `hp` belongs to it.
+    pub layers: Vec<Material>,
+    pub hp: u8,
+    pub max_hp: u8,
+    /// the wave the asteroid arrived in, which sets how many hits it takes
+    #[serde(default = "first_wave")]
+    pub wave: u32,
+    pub flash: DamageFlash,
+    /// satellites orbiting this asteroid, released when it is destroyed
+    pub satellites: Vec<Satellite>,
+    /// a gun riding on the asteroid, shooting at the ship until the
+    /// asteroid is destroyed
+    #[serde(default)]
+    pub turret: Option<Turret>,
+    pub collided: bool,
+    #[serde(default)]
+    pub time_scale: TimeScale,
+    /// where the asteroid was at the start of the last simulation step
+    #[serde(skip)]
+    pub last_pos: Point,
+    #[serde(skip)]
+    pub last_rotation: Angle,
+}
+impl Asteroid {
+    pub fn advance(&mut self, delta: f32) {
+        let delta = self.time_scale.apply(delta);
+        self.pos.x += self.vel.x * delta;
+        self.pos.y += self.vel.y * delta;
+
+        self.rotation.rotate(self.rot_speed * delta);
+    }
+
+    /// how many hits the outer layer has taken without breaking
+    pub fn damage(&self) -> u8 {
+        self.max_hp - self.hp
+    }
+
+    /// the material at the center of the asteroid, which its fragments are
+    /// made of
+    pub fn core(&self) -> Material {
+        self.layers[0]
+    }
+
+    /// whether it takes more than one hit to break
+    pub fn is_armored(&self) -> bool {
+        self.max_hp > 1 || self.layers.len() > 1
+    }
+
+    /// finds the position of one of the polygon's corners, matching the
+    /// outline drawn by `draw_poly_lines`. `scale` moves the point toward the
+    /// center (0.) or leaves it on the outline (1.).
+    pub fn vertex(&self, index: u8, scale: f32) -> Point {
+        let angle = index as f32 / self.sides as f32 * std::f32::consts::PI * 2.
+            + self.rotation.radians();
+
+        Point {
+            x: self.pos.x + angle.cos() * self.size * scale,
+            y: self.pos.y + angle.sin() * self.size * scale,
+        }
+    }
+
+    /// the corners of the asteroid's outer layer, in order around it
+    pub fn outline(&self) -> Vec<Point> {
+        (0..self.sides).map(|corner| self.vertex(corner, 1.)).collect()
+    }
+
+    /// a smaller piece of this asteroid, knocked loose by a bullet moving at
+    /// `impact` and flung out by `explosiveness`, in pixels per second
+    pub fn fragment(&self, impact: Velocity, explosiveness: f32, rng: &mut Rng) -> Asteroid {
+        let sides = self.sides - 1;
+        let rotation = rng.angle();
+        Asteroid {
+            pos: self.pos,
+            vel: Velocity {
+                x: impact.x / 5. + (self.vel.x + explosiveness) * rng.range(0., 2.),
+                y: impact.y / 5. + (self.vel.y + explosiveness) * rng.range(0., 2.),
+            },
+            rotation,
+            rot_speed: rng.range(-120., 120.),
+            size: self.size * 0.6,
+            sides,
+            layers: vec![self.core()],
+            hp: self.core().hp(sides, self.wave),
+            max_hp: self.core().hp(sides, self.wave),
+            wave: self.wave,
+            flash: DamageFlash::default(),
+            satellites: Vec::new(),
+            turret: None,
+            collided: false,
+            // fragments keep whatever was slowing or speeding up the asteroid
+            time_scale: self.time_scale,
+            last_pos: self.pos,
+            last_rotation: rotation,
+        }
+    }
+}
+
+/// a layer of an asteroid
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Material {
+    Rock,
+    /// a brittle shell that breaks in one hit to reveal what's inside
+    Ice,
+}
+impl Material {
+    /// hits a layer of this material takes on an asteroid with `sides` sides
+    /// that arrived in `wave`. Large rocks break in one hit on the first
+    /// wave and get tougher every few waves after.
+    pub fn hp(&self, sides: u8, wave: u32) -> u8 {
+        match self {
+            Material::Rock if sides >= 6 => {
+                let extra_hits = (wave / WAVES_PER_EXTRA_HP).min(MAX_LARGE_ASTEROID_HP as u32 - 1);
+                1 + extra_hits as u8
+            }
+            Material::Rock => 1,
+            Material::Ice => 1,
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            Material::Rock => BLACK,
+            Material::Ice => Color::new(0.2, 0.5, 0.8, 1.),
+        }
+    }
+}
+
+/// a rough size name for an asteroid with this many sides
+pub fn size_name(sides: u8) -> &'static str {
+    match sides {
+        6.. => "large",
+        5 => "medium",
+        _ => "small",
+    }
+}
+
+/// an asteroid that has been announced but not yet placed in the world.
+/// A marker flashes at its position until `spawn_time` so the player can see
+/// where it will appear.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpawnWarning {
+    pub asteroid: Asteroid,
+    pub spawn_time: f64,
+    /// scale of the marker ring
+    pub pulse: Animation,
+    /// opacity of the marker, blinking a few times a second
+    pub blink: Animation,
+}
+impl SpawnWarning {
+    pub fn new(asteroid: Asteroid, now: f64) -> SpawnWarning {
+        SpawnWarning {
+            asteroid,
+            spawn_time: now + SPAWN_WARNING_TIME,
+            pulse: Animation::new(vec![(0., 1.), (0.125, 1.15), (0.25, 1.)], true),
+            blink: Animation::new(vec![(0., 1.), (0.12, 1.), (0.13, 0.), (0.25, 0.)], true),
+        }
+    }
+}
+
+/// how big a freshly spawned asteroid is, for the size of the screen
+pub fn starting_size() -> f32 {
+    let (width, height) = field_size();
+    width.min(height) / 10.
+}
+
+/// a starting asteroid for `wave` anywhere on the screen at least
+/// `avoid_distance` clear of `avoid_point`
+pub fn generate_asteroid(avoid_point: Point, avoid_distance: f32, wave: u32, rng: &mut Rng) -> Asteroid {
+    let pos = rng.point_clear_of(&[(avoid_point, starting_size() + avoid_distance)]);
+    asteroid_at(pos, wave, rng)
+}
+
+/// a starting asteroid for `wave` at `pos`, drifting off in a random
+/// direction
+pub fn asteroid_at(asteroid_pos: Point, wave: u32, rng: &mut Rng) -> Asteroid {
+    let asteroid_size = starting_size();
+
+    // some asteroids have an icy shell around their rocky core
+    let mut layers = vec![Material::Rock];
+    let mut size = asteroid_size;
+    if rng.chance(ICE_SHELL_CHANCE) {
+        layers.push(Material::Ice);
+        size /= CORE_SCALE;
+    }
+    let sides = 6;
+    let hp = layers[layers.len() - 1].hp(sides, wave);
+
+    let mut satellites = Vec::new();
+    if rng.chance(SATELLITE_CHANCE) {
+        // usually a lone satellite, sometimes a pair
+        let count = WeightedTable::new(vec![(2., 1), (1., 2)]);
+        for _ in 0..*count.pick(rng) {
+            let radius = size * rng.gaussian(1.6, 0.1);
+            satellites.push(Satellite::new(radius, rng));
+        }
+    }
+
+    let rotation = rng.angle();
+    Asteroid {
+        pos: asteroid_pos,
+        vel: rng.velocity(10., 70.),
+        rotation,
+        rot_speed: rng.range(-60., 60.),
+        size,
+        sides,
+        layers,
+        hp,
+        max_hp: hp,
+        wave,
+        flash: DamageFlash::default(),
+        satellites,
+        turret: rng.chance(TURRET_CHANCE).then(|| Turret::new(rng)),
+        collided: false,
+        time_scale: TimeScale::default(),
+        last_pos: asteroid_pos,
+        last_rotation: rotation,
+    }
+}
+
+fn first_wave() -> u32 {
+    1
+}
@@ -0,0 +1,290 @@
+//! sounds built in code, so the game doesn't need to ship audio files, and
+//! the buses they're played through
+
+use crate::settings::Settings;
+use macroquad::audio::{play_sound, set_sound_volume, PlaySoundParams, Sound};
+use macroquad::prelude::*;
+
+const SAMPLE_RATE: u32 = 22050;
+/// how much each press of a volume key changes the volume
+pub const VOLUME_STEP: f32 = 0.1;
+/// seconds the volume stays on screen after it changes
+const VOLUME_DISPLAY_TIME: f64 = 1.5;
+
+/// the background loop, as frequencies and how many beats each is held
+const THEME: &[(f32, f32)] = &[
+    (110.00, 1.),
+    (130.81, 1.),
+    (164.81, 1.),
+    (130.81, 1.),
+    (98.00, 1.),
+    (123.47, 1.),
+    (146.83, 1.),
+    (123.47, 1.),
+    (87.31, 1.),
+    (110.00, 1.),
+    (130.81, 1.),
+    (110.00, 1.),
+    (82.41, 2.),
+    (98.00, 2.),
+];
+/// seconds per beat of the theme
+const THEME_BEAT: f32 = 0.45;
+/// a quick rising arpeggio for an extra life
+const JINGLE: &[(f32, f32)] = &[(523.25, 1.), (659.25, 1.), (783.99, 1.), (1046.5, 3.)];
+const JINGLE_BEAT: f32 = 0.08;
+
+/// lives left, counting the ship in play, when the heartbeat starts
+const HEARTBEAT_LIVES: u8 = 2;
+/// seconds between the first beats with two lives left. The gap is halved
+/// on the last life.
+const HEARTBEAT_START: f64 = 1.;
+/// how much each beat shortens the gap to the next
+const HEARTBEAT_SPEEDUP: f64 = 0.97;
+const HEARTBEAT_FASTEST: f64 = 0.3;
+/// seconds the HUD pulse takes to fade after a beat
+const HEARTBEAT_PULSE_TIME: f64 = 0.25;
+
+/// builds a short, decaying sine tone as an in-memory 16 bit mono WAV file
+pub fn tone_wav(frequency: f32, duration: f32) -> Vec<u8> {
+    let samples = (SAMPLE_RATE as f32 * duration) as u32;
+    wav(&(0..samples)
+        .map(|i| {
+            let t = i as f32 / SAMPLE_RATE as f32;
+            let envelope = 1. - t / duration;
+            (t * frequency * std::f32::consts::PI * 2.).sin() * envelope
+        })
+        .collect::<Vec<_>>())
+}
+
+/// builds the background theme as a WAV file that loops cleanly
+pub fn theme_wav() -> Vec<u8> {
+    melody_wav(THEME, THEME_BEAT)
+}
+
+pub fn jingle_wav() -> Vec<u8> {
+    melody_wav(JINGLE, JINGLE_BEAT)
+}
+
+/// plays `notes` one after another as a WAV file, holding each for its
+/// number of beats. Each note fades in and out so there's no click between
+/// them.
+fn melody_wav(notes: &[(f32, f32)], beat: f32) -> Vec<u8> {
+    let mut samples = Vec::new();
+    for &(frequency, beats) in notes {
+        let duration = beats * beat;
+        let count = (SAMPLE_RATE as f32 * duration) as u32;
+        for i in 0..count {
+            let t = i as f32 / SAMPLE_RATE as f32;
+            let envelope = (t / 0.02).min(1.) * (1. - t / duration).powi(2);
+            samples.push((t * frequency * std::f32::consts::PI * 2.).sin() * envelope);
+        }
+    }
+    wav(&samples)
+}
+
+/// wraps samples from -1. to 1. up as a 16 bit mono WAV file, at half volume
+fn wav(samples: &[f32]) -> Vec<u8> {
+    let data_len = samples.len() as u32 * 2;
+
+    let mut wav = Vec::with_capacity(44 + data_len as usize);
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    wav.extend_from_slice(&1u16.to_le_bytes()); // mono
+    wav.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    wav.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());
+    wav.extend_from_slice(&2u16.to_le_bytes());
+    wav.extend_from_slice(&16u16.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_len.to_le_bytes());
+
+    for sample in samples {
+        wav.extend_from_slice(&((sample * i16::MAX as f32 * 0.5) as i16).to_le_bytes());
+    }
+
+    wav
+}
+
+/// a group of sounds played at one volume, so music and sound effects can
+/// be turned up and down separately
+pub struct AudioBus {
+    volume: f32,
+    /// looping sounds on this bus, which follow its volume as it changes
+    looping: Vec<Sound>,
+}
+impl AudioBus {
+    pub fn new(volume: f32) -> AudioBus {
+        AudioBus {
+            volume,
+            looping: Vec::new(),
+        }
+    }
+
+    pub fn play_once(&self, sound: Sound) {
+        play_sound(
+            sound,
+            PlaySoundParams {
+                looped: false,
+                volume: self.volume,
+            },
+        );
+    }
+
+    pub fn play_looped(&mut self, sound: Sound) {
+        play_sound(
+            sound,
+            PlaySoundParams {
+                looped: true,
+                volume: self.volume,
+            },
+        );
+        self.looping.push(sound);
+    }
+
+    pub fn set_volume(&mut self, volume: f32) {
+        self.volume = volume;
+        for sound in self.looping.iter() {
+            set_sound_volume(*sound, self.volume);
+        }
+    }
+}
+
+/// the two-tone heartbeat that plays once lives run low. It speeds up with
+/// every beat, and starts over faster when another life is lost.
+pub struct Heartbeat {
+    tones: [Option<Sound>; 2],
+    /// the lives it is beating for, or 0 while it is quiet
+    lives: u8,
+    gap: f64,
+    last_beat: f64,
+    beats: usize,
+}
+impl Heartbeat {
+    pub fn new(tones: [Option<Sound>; 2]) -> Heartbeat {
+        Heartbeat {
+            tones,
+            lives: 0,
+            gap: HEARTBEAT_START,
+            last_beat: f64::MIN,
+            beats: 0,
+        }
+    }
+
+    /// keeps time with `lives` left, returning the tone to play if a beat
+    /// is due at `now`
+    pub fn update(&mut self, lives: u8, now: f64) -> Option<Sound> {
+        if lives == 0 || lives > HEARTBEAT_LIVES {
+            self.lives = 0;
+            return None;
+        }
+        // a life was lost or won, or a new run started the clock over
+        if lives != self.lives || now < self.last_beat {
+            self.lives = lives;
+            self.gap = HEARTBEAT_START * lives as f64 / HEARTBEAT_LIVES as f64;
+            self.last_beat = now - self.gap;
+            self.beats = 0;
+        }
+        if now - self.last_beat < self.gap {
+            return None;
+        }
+
+        self.last_beat = now;
+        self.gap = (self.gap * HEARTBEAT_SPEEDUP).max(HEARTBEAT_FASTEST);
+        self.beats += 1;
+        self.tones[self.beats % 2]
+    }
+
+    /// how strongly the HUD pulses at `now`, from 1. on a beat down to 0.
+    pub fn pulse(&self, now: f64) -> f32 {
+        if self.lives == 0 {
+            return 0.;
+        }
+        (1. - (now - self.last_beat) / HEARTBEAT_PULSE_TIME).max(0.) as f32
+    }
+}
+
+/// the game's sounds and the buses they play through
+pub struct Audio {
+    pub music: AudioBus,
+    pub effects: AudioBus,
+    pub crit: Option<Sound>,
+    pub extra_life: Option<Sound>,
+    pub heartbeat: Heartbeat,
+    /// when a volume last changed, to show it for a moment
+    volume_changed: f64,
+}
+impl Audio {
+    /// starts the theme playing, if there is one. A missing audio device
+    /// shouldn't stop the game, so every sound is optional.
+    pub fn new(
+        theme: Option<Sound>,
+        crit: Option<Sound>,
+        extra_life: Option<Sound>,
+        heartbeat: [Option<Sound>; 2],
+    ) -> Audio {
+        let mut music = AudioBus::new(0.5);
+        if let Some(theme) = theme {
+            music.play_looped(theme);
+        }
+
+        Audio {
+            music,
+            effects: AudioBus::new(1.),
+            crit,
+            extra_life,
+            heartbeat: Heartbeat::new(heartbeat),
+            volume_changed: f64::MIN,
+        }
+    }
+
+    /// sets the buses to the volumes in `settings`
+    pub fn apply_volumes(&mut self, settings: &Settings) {
+        self.music.set_volume(settings.music_volume);
+        self.effects.set_volume(settings.effects_volume);
+    }
+
+    /// minus and equals turn the music down and up, [ and ] the sound
+    /// effects. Returns whether either volume changed.
+    pub fn handle_volume_keys(&mut self, settings: &mut Settings) -> bool {
+        let changes = [
+            (KeyCode::Minus, -VOLUME_STEP, false),
+            (KeyCode::Equal, VOLUME_STEP, false),
+            (KeyCode::LeftBracket, -VOLUME_STEP, true),
+            (KeyCode::RightBracket, VOLUME_STEP, true),
+        ];
+        let mut changed = false;
+        for (key, change, effects) in changes {
+            if is_key_pressed(key) {
+                let volume = if effects {
+                    &mut settings.effects_volume
+                } else {
+                    &mut settings.music_volume
+                };
+                *volume = (*volume + change).clamp(0., 1.);
+                changed = true;
+            }
+        }
+        if changed {
+            self.apply_volumes(settings);
+            self.volume_changed = get_time();
+        }
+        changed
+    }
+
+    /// shows both volumes in the corner for a moment after either changes
+    pub fn draw_volume(&self) {
+        if get_time() - self.volume_changed > VOLUME_DISPLAY_TIME {
+            return;
+        }
+        let text = format!(
+            "Music {:.0}%  Effects {:.0}%",
+            self.music.volume * 100.,
+            self.effects.volume * 100.
+        );
+        let size = measure_text(&text, None, 18, 1.0);
+        draw_text(&text, screen_width() - size.width - 10., screen_height() - 10., 18., DARKGRAY);
+    }
+}
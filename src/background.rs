@@ -0,0 +1,68 @@
+//! faint asteroids far behind the playfield
+
+use crate::physics::{Point, Velocity};
+use crate::random;
+use macroquad::prelude::*;
+
+pub const BACKGROUND_ASTEROIDS: usize = 5;
+const BACKGROUND_PARALLAX: f32 = 0.08;
+
+/// a large, faint asteroid far behind the playfield. It never collides with
+/// anything; it drifts slowly and shifts against the ship's movement to give
+/// the scene some depth.
+pub struct BackgroundAsteroid {
+    pub pos: Point,
+    pub vel: Velocity,
+    pub rotation: f32,
+    pub rot_speed: f32,
+    pub size: f32,
+    pub sides: u8,
+    /// 0. is furthest away, 1. is closest
+    pub depth: f32,
+}
+impl BackgroundAsteroid {
+    pub fn new() -> BackgroundAsteroid {
+        random::effects(|rng| {
+            let depth = rng.range(0., 1.);
+            BackgroundAsteroid {
+                pos: rng.point_on_screen(),
+                vel: rng.velocity(2., 9.),
+                rotation: rng.angle().degrees(),
+                rot_speed: rng.range(-3., 3.),
+                size: screen_width().min(screen_height()) * (0.15 + depth * 0.15),
+                sides: rng.range(6., 10.) as u8,
+                depth,
+            }
+        })
+    }
+
+    pub fn advance(&mut self, delta: f32) {
+        self.pos.x += self.vel.x * delta;
+        self.pos.y += self.vel.y * delta;
+        self.rotation += self.rot_speed * delta;
+
+        // wrap only once fully off screen so big silhouettes don't pop
+        let (width, height) = (screen_width(), screen_height());
+        if self.pos.x > width + self.size {
+            self.pos.x = -self.size;
+        } else if self.pos.x < -self.size {
+            self.pos.x = width + self.size;
+        }
+        if self.pos.y > height + self.size {
+            self.pos.y = -self.size;
+        } else if self.pos.y < -self.size {
+            self.pos.y = height + self.size;
+        }
+    }
+
+    /// draws the silhouette offset against the ship's distance from center
+    pub fn draw(&self, ship_pos: Point) {
+        let parallax = BACKGROUND_PARALLAX * (0.5 + self.depth);
+        let x = self.pos.x - (ship_pos.x - screen_width() / 2.) * parallax;
+        let y = self.pos.y - (ship_pos.y - screen_height() / 2.) * parallax;
+        // closer silhouettes are a little darker
+        let shade = 0.75 - self.depth * 0.04;
+
+        draw_poly(x, y, self.sides, self.size, self.rotation, Color::new(shade, shade, shade, 1.));
+    }
+}
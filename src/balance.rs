@@ -0,0 +1,54 @@
+//! numbers for tuning how the game plays, read from a file so they can be
+//! changed without rebuilding
+
+use crate::scoring::EXTRA_LIFE_POINTS;
+use crate::storage::Storage;
+use serde::{Deserialize, Serialize};
+
+const BALANCE_FILE: &str = "balance.toml";
+
+/// the tunable numbers in the balance file. Any left out keep their
+/// defaults.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Balance {
+    /// points between each bonus life, or 0 for none
+    pub extra_life_points: u32,
+}
+impl Default for Balance {
+    fn default() -> Balance {
+        Balance {
+            extra_life_points: EXTRA_LIFE_POINTS,
+        }
+    }
+}
+impl Balance {
+    /// the numbers in the balance file. A missing file is written out with
+    /// the defaults so there's something to edit. Returns a notice for the
+    /// player if the file couldn't be used.
+    pub fn load(storage: &dyn Storage) -> (Balance, Option<String>) {
+        let text = match storage.read(BALANCE_FILE) {
+            Ok(Some(text)) => text,
+            Ok(None) => {
+                // if this fails the defaults still work, and it's tried
+                // again next time
+                if let Ok(text) = toml::to_string(&Balance::default()) {
+                    let _ = storage.write(BALANCE_FILE, text.as_bytes());
+                }
+                return (Balance::default(), None);
+            }
+            Err(err) => return (Balance::default(), Some(format!("{}. Using the default balance.", err))),
+        };
+        match toml::from_str(&text) {
+            Ok(balance) => (balance, None),
+            Err(err) => {
+                let notice = format!(
+                    "{} isn't valid, so the default balance is in use: {}",
+                    storage.path(BALANCE_FILE).display(),
+                    err.message()
+                );
+                (Balance::default(), Some(notice))
+            }
+        }
+    }
+}
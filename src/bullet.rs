@@ -0,0 +1,153 @@
+//! bullets fired by the ship and its enemies
+
+use crate::angle::Angle;
+use crate::collision::CollisionLayer;
+use crate::physics::{Point, TimeScale, Velocity};
+use crate::ship::Ship;
+use serde::{Deserialize, Serialize};
+
+pub const TIME_BETWEEN_SHOTS: f64 = 0.2;
+pub const BULLET_DAMAGE: u8 = 1;
+/// seconds a bullet flies before it fizzles out
+pub const BULLET_LIFETIME: f64 = 1.5;
+/// pixels per second, on top of the ship's own speed
+const BULLET_SPEED: f32 = 140.;
+/// pixels per second for a saucer's shots, a little slower than the ship's
+/// so there's time to see them coming
+pub const SAUCER_SHOT_SPEED: f32 = 120.;
+/// pixels per second for a turret's shots, slow enough to dodge
+const TURRET_SHOT_SPEED: f32 = 70.;
+/// seconds a turret's shot flies, long enough to cross most of the screen
+const TURRET_SHOT_LIFETIME: f64 = 4.;
+/// how far ahead a new bullet starts, in seconds of flight, so it clears the
+/// ship's nose
+const BULLET_HEAD_START: f32 = 1. / 30.;
+pub const MISSILE_DAMAGE: u8 = 2;
+/// seconds a missile hunts for a target before it burns out
+const MISSILE_LIFETIME: f64 = 3.;
+/// degrees per second a missile can turn toward its target
+const MISSILE_TURN_RATE: f32 = 150.;
+
+/// what a gun sends out
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Projectile {
+    /// flies straight
+    #[default]
+    Shot,
+    /// steers toward the nearest asteroid
+    Missile,
+    /// a saucer's shot at the ship
+    SaucerShot,
+    /// an asteroid turret's slow shot at the ship
+    TurretShot,
+}
+impl Projectile {
+    /// damage to an asteroid it hits
+    pub fn damage(&self) -> u8 {
+        match self {
+            Projectile::Shot => BULLET_DAMAGE,
+            Projectile::Missile => MISSILE_DAMAGE,
+            // enemy shots only ever hit the ship
+            Projectile::SaucerShot | Projectile::TurretShot => 0,
+        }
+    }
+
+    /// which side fired it, for what it can run into
+    pub fn layer(&self) -> CollisionLayer {
+        match self {
+            Projectile::Shot | Projectile::Missile => CollisionLayer::Player,
+            Projectile::SaucerShot | Projectile::TurretShot => CollisionLayer::Enemy,
+        }
+    }
+
+    /// seconds it flies before it's gone
+    pub fn lifetime(&self) -> f64 {
+        match self {
+            Projectile::Shot | Projectile::SaucerShot => BULLET_LIFETIME,
+            Projectile::Missile => MISSILE_LIFETIME,
+            Projectile::TurretShot => TURRET_SHOT_LIFETIME,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bullet {
+    pub pos: Point,
+    pub vel: Velocity,
+    pub initial_frame: f64,
+    pub collided: bool,
+    #[serde(default)]
+    pub projectile: Projectile,
+    #[serde(default)]
+    pub time_scale: TimeScale,
+    /// where the bullet was at the start of the last simulation step
+    #[serde(skip)]
+    pub last_pos: Point,
+}
+impl Bullet {
+    /// a bullet leaving the nose of the ship toward `aim`
+    pub fn fire(ship: &Ship, aim: Angle, now: f64) -> Bullet {
+        let mut velocity = Velocity::default();
+        velocity.add_at_angle(BULLET_SPEED, aim.degrees());
+
+        let mut bullet = Bullet {
+            pos: ship.pos,
+            vel: velocity,
+            initial_frame: now,
+            collided: false,
+            projectile: Projectile::Shot,
+            time_scale: ship.time_scale,
+            last_pos: ship.pos,
+        };
+
+        // advance the bullet to get it past the ship.
+        bullet.advance(BULLET_HEAD_START);
+        bullet.vel.add_velocity(ship.vel);
+        bullet
+    }
+
+    /// an enemy's shot, leaving `pos` toward `aim`
+    pub fn aimed(pos: Point, aim: Angle, projectile: Projectile, time_scale: TimeScale, now: f64) -> Bullet {
+        let speed = match projectile {
+            Projectile::TurretShot => TURRET_SHOT_SPEED,
+            _ => SAUCER_SHOT_SPEED,
+        };
+        let mut velocity = Velocity::default();
+        velocity.add_at_angle(speed, aim.degrees());
+        Bullet {
+            pos,
+            vel: velocity,
+            initial_frame: now,
+            collided: false,
+            projectile,
+            time_scale,
+            last_pos: pos,
+        }
+    }
+
+    /// a missile leaving the nose of the ship the way it's facing
+    pub fn missile(ship: &Ship, now: f64) -> Bullet {
+        Bullet {
+            projectile: Projectile::Missile,
+            ..Bullet::fire(ship, ship.rotation, now)
+        }
+    }
+
+    /// turns toward `target` as far as a missile can over `delta` seconds,
+    /// keeping its speed
+    pub fn home_in(&mut self, target: Point, delta: f32) {
+        let max_turn = MISSILE_TURN_RATE * self.time_scale.apply(delta);
+        let speed = self.vel.x.hypot(self.vel.y);
+        let mut heading = Angle::towards(Point::default(), Point { x: self.vel.x, y: self.vel.y });
+        let turn = heading.shortest_difference(Angle::towards(self.pos, target));
+        heading.rotate(turn.clamp(-max_turn, max_turn));
+        self.vel = Velocity::default();
+        self.vel.add_at_angle(speed, heading.degrees());
+    }
+
+    pub fn advance(&mut self, delta: f32) {
+        let delta = self.time_scale.apply(delta);
+        self.pos.x += self.vel.x * delta;
+        self.pos.y += self.vel.y * delta;
+    }
+}
@@ -0,0 +1,87 @@
+//! the "what's new" screen, built from the bundled changelog
+
+use macroquad::prelude::*;
+use serde::Deserialize;
+
+const CHANGELOG: &str = include_str!("../changelog.json");
+/// pixels a second the changelog scrolls while Up or Down is held
+const SCROLL_SPEED: f32 = 300.;
+/// where the first version's heading goes, below the title bar
+const TOP: f32 = 60.;
+const VERSION_HEIGHT: f32 = 30.;
+const CHANGE_HEIGHT: f32 = 22.;
+/// space after each version's list of changes
+const VERSION_GAP: f32 = 20.;
+
+/// one release's worth of changes in the bundled changelog
+#[derive(Debug, Deserialize)]
+pub struct ChangelogEntry {
+    pub version: String,
+    pub changes: Vec<String>,
+}
+
+/// the what's new screen while it's open. The changelog is read once, when
+/// the screen is opened.
+pub struct ChangelogScreen {
+    entries: Result<Vec<ChangelogEntry>, String>,
+    /// pixels scrolled down from the top
+    scroll: f32,
+}
+impl ChangelogScreen {
+    pub fn open() -> ChangelogScreen {
+        ChangelogScreen {
+            entries: serde_json::from_str(CHANGELOG).map_err(|err| err.to_string()),
+            scroll: 0.,
+        }
+    }
+
+    /// scrolls with Up and Down, no further than it takes to bring the last
+    /// change onto the screen
+    pub fn update(&mut self) {
+        if is_key_down(KeyCode::Down) {
+            self.scroll += SCROLL_SPEED * get_frame_time();
+        } else if is_key_down(KeyCode::Up) {
+            self.scroll -= SCROLL_SPEED * get_frame_time();
+        }
+        let max_scroll = (TOP + self.height() - screen_height()).max(0.);
+        self.scroll = self.scroll.clamp(0., max_scroll);
+    }
+
+    /// how tall the list of changes is when drawn
+    fn height(&self) -> f32 {
+        let Ok(entries) = &self.entries else {
+            return 0.;
+        };
+        entries
+            .iter()
+            .map(|entry| VERSION_HEIGHT + entry.changes.len() as f32 * CHANGE_HEIGHT + VERSION_GAP)
+            .sum()
+    }
+
+    pub fn draw(&self) {
+        clear_background(LIGHTGRAY);
+
+        let entries = match &self.entries {
+            Ok(entries) => entries,
+            Err(err) => {
+                draw_text(&format!("Couldn't read the changelog: {}", err), 20., 40., 20., RED);
+                return;
+            }
+        };
+
+        let mut y = TOP - self.scroll;
+        for entry in entries.iter() {
+            draw_text(&entry.version, 20., y, 30., BLACK);
+            y += VERSION_HEIGHT;
+            for change in entry.changes.iter() {
+                draw_text(&format!("- {}", change), 35., y, 20., DARKGRAY);
+                y += CHANGE_HEIGHT;
+            }
+            y += VERSION_GAP;
+        }
+
+        // keep the title and controls readable over the scrolled text
+        draw_rectangle(0., 0., screen_width(), 30., LIGHTGRAY);
+        draw_text("What's new (Up/Down to scroll, Escape to go back)", 20., 22., 20., BLACK);
+    }
+}
@@ -0,0 +1,722 @@
+//! what happens when things in the world run into each other
+
+use crate::angle::Angle;
+use crate::asteroid::{Asteroid, CORE_SCALE};
+use crate::bullet::Projectile;
+use crate::effects::{damage_color, CritFlash, Effects, FloatingText, ShieldRipple, Shockwave};
+use crate::events::GameEvent;
+use crate::physics::{field_size, Point, Velocity, TICK};
+use crate::powerup::{
+    PowerUp, PowerUpKind, MISSILE_AMMO, POWERUP_DROP_CHANCE, POWERUP_SIZE, RAPID_FIRE_TIME,
+    SPREAD_SHOT_TIME,
+};
+use crate::random::Rng;
+use crate::satellite::{MAX_CRIT_CHANCE, SATELLITE_CRIT_BONUS, SATELLITE_SIZE};
+use crate::ship::{Direction, Ship, SHIP_HEIGHT};
+use crate::weapon::Weapon;
+use crate::world::World;
+use macroquad::prelude::*;
+use std::collections::HashMap;
+
+/// particles thrown off by a destroyed asteroid for each of its sides, so
+/// bigger asteroids make a bigger mess
+const DEBRIS_PER_SIDE: usize = 3;
+const SHIP_DEBRIS: usize = 30;
+const SAUCER_DEBRIS: usize = 20;
+/// how far from the ship a smart bomb reaches
+const BOMB_RADIUS: f32 = 220.;
+/// how hard a smart bomb flings the pieces of what it breaks, like a
+/// bullet's speed
+const BOMB_PUSH: f32 = 300.;
+/// how close two projectiles have to pass to knock each other out
+const INTERCEPT_RADIUS: f32 = 6.;
+/// sparks thrown off when one projectile shoots down another
+const INTERCEPT_SPARKS: usize = 6;
+/// the pairs of collision layers whose projectiles destroy each other on
+/// contact. Anything not listed passes straight through.
+const PROJECTILE_INTERCEPTS: &[(CollisionLayer, CollisionLayer)] =
+    &[(CollisionLayer::Player, CollisionLayer::Enemy)];
+/// pixels across each cell of the spatial hash, about the size of a large
+/// asteroid so most things only touch a few cells
+const CELL_SIZE: f32 = 80.;
+
+/// which side something in the world is on, for deciding what it can
+/// collide with
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CollisionLayer {
+    /// the ship and what it fires
+    Player,
+    /// saucers, turrets and what they fire
+    Enemy,
+}
+
+/// whether projectiles on layers `a` and `b` destroy each other on contact
+fn projectiles_intercept(a: CollisionLayer, b: CollisionLayer) -> bool {
+    PROJECTILE_INTERCEPTS
+        .iter()
+        .any(|&pair| pair == (a, b) || pair == (b, a))
+}
+
+/// an axis-aligned bounding box. Checking a point or box against one is a
+/// few comparisons, so it's a cheap first test before any exact one.
+#[derive(Debug, Clone, Copy)]
+pub struct Aabb {
+    pub min: Point,
+    pub max: Point,
+}
+impl Aabb {
+    /// the smallest box holding all of `points`
+    pub fn around(points: impl IntoIterator<Item = Point>) -> Aabb {
+        let mut bounds = Aabb {
+            min: Point { x: f32::MAX, y: f32::MAX },
+            max: Point { x: f32::MIN, y: f32::MIN },
+        };
+        for point in points {
+            bounds.min.x = bounds.min.x.min(point.x);
+            bounds.min.y = bounds.min.y.min(point.y);
+            bounds.max.x = bounds.max.x.max(point.x);
+            bounds.max.y = bounds.max.y.max(point.y);
+        }
+        bounds
+    }
+
+    pub fn contains(&self, point: &Point) -> bool {
+        point.x >= self.min.x && point.x <= self.max.x && point.y >= self.min.y && point.y <= self.max.y
+    }
+}
+
+/// a grid over the world that remembers which things touch which of its
+/// cells, so a collision check only has to look at things nearby rather than
+/// at everything
+#[derive(Default)]
+pub struct SpatialHash {
+    cells: HashMap<(i32, i32), Vec<usize>>,
+}
+impl SpatialHash {
+    /// adds the thing at `index` to every cell `bounds` touches
+    pub fn insert(&mut self, index: usize, bounds: &Aabb) {
+        for cell in cells_under(bounds) {
+            self.cells.entry(cell).or_default().push(index);
+        }
+    }
+
+    /// everything in the cells `bounds` touches, each once, in index order
+    pub fn query(&self, bounds: &Aabb) -> Vec<usize> {
+        let mut found: Vec<usize> = cells_under(bounds)
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .copied()
+            .collect();
+        found.sort_unstable();
+        found.dedup();
+        found
+    }
+}
+
+fn cells_under(bounds: &Aabb) -> impl Iterator<Item = (i32, i32)> {
+    let cell = |value: f32| (value / CELL_SIZE).floor() as i32;
+    let (left, right) = (cell(bounds.min.x), cell(bounds.max.x));
+    let (top, bottom) = (cell(bounds.min.y), cell(bounds.max.y));
+    (left..=right).flat_map(move |x| (top..=bottom).map(move |y| (x, y)))
+}
+
+/// whether `point` is inside `polygon`, by counting how many of its edges a
+/// line running right from the point crosses: an odd number means inside
+pub fn point_in_polygon(point: &Point, polygon: &[Point]) -> bool {
+    let mut inside = false;
+    let mut previous = polygon[polygon.len() - 1];
+    for corner in polygon {
+        if (corner.y > point.y) != (previous.y > point.y)
+            && point.x < (previous.x - corner.x) * (point.y - corner.y) / (previous.y - corner.y) + corner.x
+        {
+            inside = !inside;
+        }
+        previous = *corner;
+    }
+    inside
+}
+
+/// whether two convex polygons overlap. They don't exactly when there's a
+/// gap between them along the normal of one of their edges.
+pub fn polygons_overlap(a: &[Point], b: &[Point]) -> bool {
+    !has_separating_edge(a, b) && !has_separating_edge(b, a)
+}
+
+/// whether one of `polygon`'s edges has all of `other` off past its side
+fn has_separating_edge(polygon: &[Point], other: &[Point]) -> bool {
+    (0..polygon.len()).any(|edge| {
+        let start = polygon[edge];
+        let end = polygon[(edge + 1) % polygon.len()];
+        let normal = Point {
+            x: start.y - end.y,
+            y: end.x - start.x,
+        };
+        let extent = |points: &[Point]| {
+            points.iter().fold((f32::MAX, f32::MIN), |(min, max), point| {
+                let projected = point.x * normal.x + point.y * normal.y;
+                (min.min(projected), max.max(projected))
+            })
+        };
+        let (min, max) = extent(polygon);
+        let (other_min, other_max) = extent(other);
+        max < other_min || other_max < min
+    })
+}
+
+/// something striking an asteroid: where, how fast it was going, and how
+/// hard it hits
+#[derive(Debug, Clone, Copy)]
+struct Strike {
+    pos: Point,
+    vel: Velocity,
+    damage: u8,
+}
+
+/// the outcome of a bullet striking an asteroid
+pub struct Hit {
+    pub damage: u8,
+    pub critical: bool,
+    /// the outer layer broke away, revealing the one beneath
+    pub shell_broken: bool,
+    pub destroyed: bool,
+}
+
+/// applies a bullet's damage to an asteroid. Critical hits deal double
+/// damage, which is enough to shatter a two-hit asteroid outright. Breaking
+/// an outer layer leaves the smaller layer beneath it at full strength.
+pub fn resolve_hit(asteroid: &mut Asteroid, damage: u8, crit_chance: f32, rng: &mut Rng) -> Hit {
+    let critical = rng.chance(crit_chance);
+    let damage = if critical { damage * 2 } else { damage };
+    asteroid.hp = asteroid.hp.saturating_sub(damage);
+
+    let shell_broken = asteroid.hp == 0 && asteroid.layers.len() > 1;
+    if shell_broken {
+        asteroid.layers.pop();
+        asteroid.size *= CORE_SCALE;
+        asteroid.max_hp = asteroid.layers[asteroid.layers.len() - 1].hp(asteroid.sides, asteroid.wave);
+        asteroid.hp = asteroid.max_hp;
+    }
+
+    Hit {
+        damage,
+        critical,
+        shell_broken,
+        destroyed: asteroid.hp == 0,
+    }
+}
+
+/// checks the ship and bullets against every asteroid and the saucer,
+/// breaking up whatever was hit, and lets the ship's bullets shoot down
+/// enemy shots, then collects any loose satellites the ship
+/// flies through. Returns whether the ship was hit.
+pub fn check_collisions(
+    world: &mut World,
+    effects: &mut Effects,
+    events: &mut Vec<GameEvent>,
+    show_damage_numbers: bool,
+    now: f64,
+) -> bool {
+    let mut ship_hit = false;
+    // whether nothing more can hit the ship this step
+    let mut ship_safe = world.ship.is_invulnerable(now);
+    // the asteroids destroyed this step and the speed of what hit them, to
+    // break up once they've all been checked
+    let mut broken = Vec::new();
+
+    // most things are nowhere near most asteroids, so only the asteroids in
+    // the grid cells around something are checked against it
+    let outlines: Vec<Vec<Point>> = world.asteroids.iter().map(Asteroid::outline).collect();
+    let bounds: Vec<Aabb> = outlines.iter().map(|outline| Aabb::around(outline.iter().copied())).collect();
+    let mut grid = SpatialHash::default();
+    for (index, asteroid_bounds) in bounds.iter().enumerate() {
+        grid.insert(index, asteroid_bounds);
+    }
+    let ship_outline = world.ship.outline();
+    let near_ship = grid.query(&Aabb::around(ship_outline));
+    let mut near_bullets = vec![Vec::new(); outlines.len()];
+    for (bullet, pos) in world.bullets.iter().map(|bullet| bullet.pos).enumerate() {
+        for asteroid in grid.query(&Aabb::around([pos])) {
+            near_bullets[asteroid].push(bullet);
+        }
+    }
+    let laser_strike = fire_laser(&mut world.ship, &outlines);
+
+    for (index, asteroid) in world.asteroids.iter_mut().enumerate() {
+        let outline = &outlines[index];
+
+        // check for asteroid strikes
+        if !ship_safe && near_ship.binary_search(&index).is_ok() && polygons_overlap(&ship_outline, outline) {
+            let destroyed = GameEvent::ShipDestroyed {
+                sides: asteroid.sides,
+                from: Direction::of(asteroid.pos, &world.ship),
+            };
+            ship_hit = strike_ship(&mut world.ship, asteroid.pos, effects, events, destroyed, now);
+            ship_safe = true;
+            if ship_hit {
+                break;
+            }
+        }
+
+        // check for asteroid hits, from the first bullet inside it or else
+        // the laser
+        let bullet_strike = near_bullets[index].iter().find_map(|&bullet_index| {
+            let bullet = &mut world.bullets[bullet_index];
+            let inside = bounds[index].contains(&bullet.pos) && point_in_polygon(&bullet.pos, outline);
+            inside.then(|| {
+                bullet.collided = true;
+                Strike {
+                    pos: bullet.pos,
+                    vel: bullet.vel,
+                    damage: bullet.projectile.damage(),
+                }
+            })
+        });
+        let laser_hit = laser_strike.filter(|(target, _)| *target == index).map(|(_, strike)| strike);
+        let Some(strike) = bullet_strike.or(laser_hit) else {
+            continue;
+        };
+
+        let armored = asteroid.is_armored();
+        let hit = resolve_hit(asteroid, strike.damage, world.ship.crit_chance, &mut world.rng);
+        if hit.critical {
+            events.push(GameEvent::CriticalHit);
+            effects.crit_flashes.push(CritFlash {
+                pos: strike.pos,
+                start_time: now,
+            });
+        }
+        if show_damage_numbers && armored {
+            effects.floating_texts.push(FloatingText {
+                text: hit.damage.to_string(),
+                pos: strike.pos,
+                color: damage_color(&hit),
+                start_time: now,
+            });
+        }
+
+        // armored asteroids crack before they break
+        if hit.shell_broken {
+            events.push(GameEvent::ShellBroken { sides: asteroid.sides });
+        }
+        if !hit.destroyed {
+            asteroid.flash.trigger(now);
+            if !hit.shell_broken {
+                events.push(GameEvent::AsteroidCracked);
+            }
+            continue;
+        }
+        asteroid.collided = true;
+        broken.push((index, strike.vel));
+    }
+    for (index, push) in broken {
+        break_up(world, index, push, effects, events, now);
+    }
+
+    // a smart bomb breaks up everything in reach however tough it is, even
+    // if the ship was hit this step
+    let bomb = world.bomb_blast.take();
+    if let Some(blast) = bomb {
+        effects.shockwaves.push(Shockwave {
+            pos: blast,
+            radius: BOMB_RADIUS,
+            start_time: now,
+        });
+        let in_reach: Vec<usize> = (0..world.asteroids.len())
+            .filter(|&index| {
+                let asteroid = &world.asteroids[index];
+                !asteroid.collided && blast.distance(&asteroid.pos) < BOMB_RADIUS + asteroid.size
+            })
+            .collect();
+        for index in in_reach {
+            world.asteroids[index].collided = true;
+            let push = bomb_push(blast, world.asteroids[index].pos);
+            break_up(world, index, push, effects, events, now);
+        }
+    }
+
+    if let Some(saucer) = world.saucer.as_mut() {
+        let size = saucer.size();
+        saucer.collided |= bomb.is_some_and(|pos| pos.distance(&saucer.pos) < BOMB_RADIUS + size);
+        for bullet in world.bullets.iter_mut() {
+            if !bullet.collided && saucer.pos.distance(&bullet.pos) < size {
+                bullet.collided = true;
+                saucer.collided = true;
+                break;
+            }
+        }
+        // ramming the saucer takes it down with the ship
+        if !ship_safe && saucer.pos.distance(&world.ship.pos) < size + SHIP_HEIGHT / 3. {
+            let destroyed = GameEvent::ShipDestroyedBySaucer {
+                from: Direction::of(saucer.pos, &world.ship),
+            };
+            ship_hit = strike_ship(&mut world.ship, saucer.pos, effects, events, destroyed, now);
+            ship_safe = true;
+            saucer.collided = true;
+        }
+        if saucer.collided {
+            events.push(GameEvent::SaucerDestroyed(saucer.kind));
+            effects
+                .particles
+                .burst(saucer.pos, saucer.vel, SAUCER_DEBRIS, DARKGRAY, now);
+        }
+    }
+    intercept_projectiles(world, effects, now);
+    if !ship_safe {
+        for shot in world.enemy_shots.iter_mut().filter(|shot| !shot.collided) {
+            if shot.pos.distance(&world.ship.pos) < SHIP_HEIGHT / 3. {
+                shot.collided = true;
+                let from = Direction::of(shot.last_pos, &world.ship);
+                let destroyed = match shot.projectile {
+                    Projectile::TurretShot => GameEvent::ShipDestroyedByTurret { from },
+                    _ => GameEvent::ShipDestroyedBySaucer { from },
+                };
+                ship_hit = strike_ship(&mut world.ship, shot.last_pos, effects, events, destroyed, now);
+                break;
+            }
+        }
+    }
+
+    // collect any loose satellites the ship flies through
+    for satellite in world.satellites.iter_mut() {
+        if satellite.pos.distance(&world.ship.pos) < SHIP_HEIGHT / 2. + SATELLITE_SIZE {
+            satellite.collected = true;
+            world.ship.crit_chance = (world.ship.crit_chance + SATELLITE_CRIT_BONUS).min(MAX_CRIT_CHANCE);
+            events.push(GameEvent::SatelliteCollected);
+        }
+    }
+
+    // and any power-ups
+    for powerup in world.powerups.iter_mut() {
+        if powerup.pos.distance(&world.ship.pos) < SHIP_HEIGHT / 2. + POWERUP_SIZE {
+            powerup.collected = true;
+            match powerup.kind {
+                PowerUpKind::Shield => world.ship.shield = true,
+                PowerUpKind::RapidFire => world.ship.rapid_fire_until = now + RAPID_FIRE_TIME,
+                PowerUpKind::SpreadShot => {
+                    world.ship.spread_until = now + SPREAD_SHOT_TIME;
+                    world.ship.weapon = Weapon::Spread;
+                }
+                PowerUpKind::Missiles => world.ship.pick_up_ammo(Weapon::Missiles, MISSILE_AMMO),
+                PowerUpKind::Bomb => world.ship.add_bomb(),
+            }
+            events.push(GameEvent::PowerUpCollected(powerup.kind));
+        }
+    }
+
+    ship_hit
+}
+
+/// knocks out the ship's bullets and the enemies' shots that run into each
+/// other, if their layers say they should, with a spark where they met
+fn intercept_projectiles(world: &mut World, effects: &mut Effects, now: f64) {
+    for bullet in world.bullets.iter_mut().filter(|bullet| !bullet.collided) {
+        let hit = world.enemy_shots.iter_mut().find(|shot| {
+            !shot.collided
+                && projectiles_intercept(bullet.projectile.layer(), shot.projectile.layer())
+                && shot.pos.distance(&bullet.pos) < INTERCEPT_RADIUS
+        });
+        if let Some(shot) = hit {
+            bullet.collided = true;
+            shot.collided = true;
+            effects
+                .particles
+                .burst(shot.pos, Velocity::default(), INTERCEPT_SPARKS, ORANGE, now);
+        }
+    }
+}
+
+/// works out how far the ship's laser reaches, to the first asteroid in its
+/// way, and burns that asteroid for a step. Returns which asteroid it is
+/// and the strike, once the burn comes to at least one whole hit.
+fn fire_laser(ship: &mut Ship, outlines: &[Vec<Point>]) -> Option<(usize, Strike)> {
+    if !ship.firing_laser {
+        return None;
+    }
+    let nose = ship.outline()[0];
+    let mut direction = Velocity::default();
+    direction.add_at_angle(1., ship.rotation.degrees());
+
+    let nearest = outlines
+        .iter()
+        .enumerate()
+        .filter_map(|(index, outline)| {
+            ray_meets_polygon(nose, direction, outline).map(|reach| (index, reach))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b));
+    ship.laser_reach = match nearest {
+        Some((_, reach)) => reach,
+        None => {
+            let (width, height) = field_size();
+            width.hypot(height)
+        }
+    };
+
+    let (target, reach) = nearest?;
+    let damage = ship.burn(TICK);
+    if damage == 0 {
+        return None;
+    }
+    let strike = Strike {
+        pos: Point {
+            x: nose.x + direction.x * reach,
+            y: nose.y + direction.y * reach,
+        },
+        // light barely pushes the pieces
+        vel: direction,
+        damage,
+    };
+    Some((target, strike))
+}
+
+/// how a smart bomb going off at `blast` flings the pieces of the asteroid
+/// at `target`: straight away from the blast
+fn bomb_push(blast: Point, target: Point) -> Velocity {
+    let distance = blast.distance(&target).max(1.);
+    Velocity {
+        x: (target.x - blast.x) / distance * BOMB_PUSH,
+        y: (target.y - blast.y) / distance * BOMB_PUSH,
+    }
+}
+
+/// finishes off the destroyed asteroid at `index`: throws out its debris,
+/// sets its satellites loose, maybe drops a power-up, and splits it into two
+/// smaller pieces flung along `push` if it isn't already the smallest size
+fn break_up(
+    world: &mut World,
+    index: usize,
+    push: Velocity,
+    effects: &mut Effects,
+    events: &mut Vec<GameEvent>,
+    now: f64,
+) {
+    let asteroid = &mut world.asteroids[index];
+    let material = asteroid.layers[asteroid.layers.len() - 1];
+    events.push(GameEvent::AsteroidDestroyed {
+        sides: asteroid.sides,
+        material,
+    });
+    let debris = asteroid.sides as usize * DEBRIS_PER_SIDE;
+    effects
+        .particles
+        .burst(asteroid.pos, asteroid.vel, debris, material.color(), now);
+    for satellite in asteroid.satellites.drain(..) {
+        world.satellites.push(satellite.release(asteroid.vel, now));
+    }
+    if world.rng.chance(POWERUP_DROP_CHANCE) {
+        let kind = PowerUpKind::random(&mut world.rng);
+        world
+            .powerups
+            .push(PowerUp::drop(kind, asteroid.pos, asteroid.vel, now, &mut world.rng));
+    }
+
+    if asteroid.sides > 4 {
+        let explosiveness = world.rng.range(0., 60.);
+        let fragments = [
+            asteroid.fragment(push, explosiveness, &mut world.rng),
+            asteroid.fragment(push, explosiveness, &mut world.rng),
+        ];
+        world.asteroids.extend(fragments);
+    }
+}
+
+/// how far along a ray from `origin` heading in `direction`, which has a
+/// length of one, it first crosses the edge of `polygon`, if it does at all
+fn ray_meets_polygon(origin: Point, direction: Velocity, polygon: &[Point]) -> Option<f32> {
+    let cross = |ax: f32, ay: f32, bx: f32, by: f32| ax * by - ay * bx;
+    (0..polygon.len())
+        .filter_map(|index| {
+            let start = polygon[index];
+            let end = polygon[(index + 1) % polygon.len()];
+            let (edge_x, edge_y) = (end.x - start.x, end.y - start.y);
+            let denominator = cross(direction.x, direction.y, edge_x, edge_y);
+            if denominator == 0. {
+                // parallel to the edge
+                return None;
+            }
+            let (to_x, to_y) = (start.x - origin.x, start.y - origin.y);
+            let along_ray = cross(to_x, to_y, edge_x, edge_y) / denominator;
+            let along_edge = cross(to_x, to_y, direction.x, direction.y) / denominator;
+            (along_ray >= 0. && (0. ..=1.).contains(&along_edge)).then_some(along_ray)
+        })
+        .min_by(f32::total_cmp)
+}
+
+/// hits the ship with something coming from `impact` that would destroy it.
+/// A shield or the energy meter takes the hit if it can, rippling on the
+/// side it came from; otherwise the ship breaks up and `destroyed` is
+/// pushed. Returns whether the ship was destroyed.
+fn strike_ship(
+    ship: &mut Ship,
+    impact: Point,
+    effects: &mut Effects,
+    events: &mut Vec<GameEvent>,
+    destroyed: GameEvent,
+    now: f64,
+) -> bool {
+    if ship.absorb_hit(now) {
+        ship.flash.trigger(now);
+        events.push(GameEvent::ShieldAbsorbed);
+        effects.shield_ripples.push(ShieldRipple {
+            angle: Angle::towards(ship.pos, impact),
+            start_time: now,
+        });
+        return false;
+    }
+    effects.particles.burst(ship.pos, ship.vel, SHIP_DEBRIS, DARKGRAY, now);
+    events.push(destroyed);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asteroid::asteroid_at;
+    use crate::bullet::Bullet;
+    use crate::random::Rng;
+    use crate::saucer::{Saucer, SaucerKind};
+    use crate::settings::{Density, Difficulty};
+
+    fn bullet_at(x: f32, projectile: Projectile) -> Bullet {
+        let pos = Point { x, y: 100. };
+        Bullet {
+            pos,
+            vel: Velocity::default(),
+            initial_frame: 0.,
+            collided: false,
+            projectile,
+            time_scale: Default::default(),
+            last_pos: pos,
+        }
+    }
+
+    #[test]
+    fn player_bullets_shoot_down_enemy_shots() {
+        let mut world = World::new(Density::Sparse, Difficulty::Normal, 1);
+        world.bullets = vec![bullet_at(100., Projectile::Shot), bullet_at(300., Projectile::Shot)];
+        world.enemy_shots = vec![
+            bullet_at(103., Projectile::SaucerShot),
+            bullet_at(300. + INTERCEPT_RADIUS * 2., Projectile::TurretShot),
+        ];
+        let mut effects = Effects::default();
+        intercept_projectiles(&mut world, &mut effects, 0.);
+
+        assert!(world.bullets[0].collided && world.enemy_shots[0].collided);
+        assert!(!world.bullets[1].collided && !world.enemy_shots[1].collided);
+    }
+
+    #[test]
+    fn only_opposing_projectiles_intercept() {
+        assert!(projectiles_intercept(CollisionLayer::Enemy, CollisionLayer::Player));
+        assert!(!projectiles_intercept(CollisionLayer::Player, CollisionLayer::Player));
+        assert!(!projectiles_intercept(CollisionLayer::Enemy, CollisionLayer::Enemy));
+    }
+
+    #[test]
+    fn bombs_break_up_everything_in_reach() {
+        let mut world = World::new(Density::Sparse, Difficulty::Normal, 6);
+        let mut rng = Rng::new(6);
+        let blast = world.ship.pos;
+        let mut near = asteroid_at(blast, 1, &mut rng);
+        near.pos.x += BOMB_RADIUS;
+        // just clear of the blast
+        let mut far = asteroid_at(blast, 1, &mut rng);
+        far.pos.x -= BOMB_RADIUS + far.size + 10.;
+        world.asteroids = vec![near, far];
+        let mut saucer = Saucer::enter(SaucerKind::Large, 0., &mut rng);
+        saucer.pos = Point {
+            x: blast.x,
+            y: blast.y - BOMB_RADIUS,
+        };
+        world.saucer = Some(saucer);
+        world.bomb_blast = Some(blast);
+
+        check_collisions(&mut world, &mut Effects::default(), &mut Vec::new(), false, 0.);
+        assert!(world.asteroids[0].collided);
+        assert!(!world.asteroids[1].collided);
+        assert!(world.saucer.is_some_and(|saucer| saucer.collided));
+    }
+
+    /// a box `size` across with its top left corner at `x`, `y`
+    fn box_at(x: f32, y: f32, size: f32) -> Aabb {
+        Aabb::around([Point { x, y }, Point { x: x + size, y: y + size }])
+    }
+
+    fn overlap(a: &Aabb, b: &Aabb) -> bool {
+        a.min.x <= b.max.x && b.min.x <= a.max.x && a.min.y <= b.max.y && b.min.y <= a.max.y
+    }
+
+    #[test]
+    fn spatial_hash_finds_what_brute_force_does() {
+        // boxes run past every edge of the field, where outlines wrap
+        let mut rng = Rng::new(5);
+        let mut random_box = || box_at(rng.range(-150., 850.), rng.range(-150., 650.), rng.range(1., 160.));
+        let boxes: Vec<Aabb> = (0..200).map(|_| random_box()).collect();
+        let mut grid = SpatialHash::default();
+        for (index, bounds) in boxes.iter().enumerate() {
+            grid.insert(index, bounds);
+        }
+
+        for _ in 0..200 {
+            let query = random_box();
+            let found = grid.query(&query);
+            for (index, bounds) in boxes.iter().enumerate() {
+                if overlap(bounds, &query) {
+                    assert!(found.contains(&index), "{:?} missed {:?}", query, bounds);
+                }
+            }
+            // and nothing more than a cell away
+            let near = Aabb::around([
+                Point {
+                    x: query.min.x - CELL_SIZE,
+                    y: query.min.y - CELL_SIZE,
+                },
+                Point {
+                    x: query.max.x + CELL_SIZE,
+                    y: query.max.y + CELL_SIZE,
+                },
+            ]);
+            assert!(found.iter().all(|&index| overlap(&boxes[index], &near)));
+        }
+    }
+
+    #[test]
+    fn spatial_hash_looks_across_cell_lines() {
+        let mut grid = SpatialHash::default();
+        // over the corner of four cells, and over the left edge of the field
+        grid.insert(0, &box_at(CELL_SIZE - 5., CELL_SIZE - 5., 10.));
+        grid.insert(1, &box_at(-10., 200., 20.));
+        assert_eq!(grid.query(&box_at(CELL_SIZE + 1., CELL_SIZE + 1., 1.)), vec![0]);
+        assert_eq!(grid.query(&box_at(1., 1., 1.)), vec![0]);
+        assert_eq!(grid.query(&box_at(-5., 210., 1.)), vec![1]);
+        assert_eq!(grid.query(&box_at(5., 210., 1.)), vec![1]);
+    }
+
+    /// a square 10 across with its top left corner at the origin
+    const SQUARE: [Point; 4] = [
+        Point { x: 0., y: 0. },
+        Point { x: 10., y: 0. },
+        Point { x: 10., y: 10. },
+        Point { x: 0., y: 10. },
+    ];
+    const RIGHT: Velocity = Velocity { x: 1., y: 0. };
+
+    #[test]
+    fn rays_stop_at_the_nearest_edge() {
+        // it crosses the far edge too, 15 along
+        let reach = ray_meets_polygon(Point { x: -5., y: 5. }, RIGHT, &SQUARE);
+        assert_eq!(reach, Some(5.));
+    }
+
+    #[test]
+    fn rays_miss_polygons_they_dont_point_at() {
+        assert_eq!(ray_meets_polygon(Point { x: -5., y: 20. }, RIGHT, &SQUARE), None);
+        let away = Velocity { x: -1., y: 0. };
+        assert_eq!(ray_meets_polygon(Point { x: -5., y: 5. }, away, &SQUARE), None);
+    }
+
+    #[test]
+    fn rays_from_inside_meet_the_edge_they_leave_by() {
+        let down = Velocity { x: 0., y: 1. };
+        assert_eq!(ray_meets_polygon(Point { x: 5., y: 2. }, down, &SQUARE), Some(8.));
+    }
+}
@@ -0,0 +1,64 @@
+//! a readable error screen for when the game can't start
+
+use crate::render::draw_centered_text;
+use macroquad::prelude::*;
+use std::path::PathBuf;
+
+/// why the game couldn't start, and the file to blame if there is one
+#[derive(Debug)]
+pub struct StartupError {
+    pub message: String,
+    pub path: Option<PathBuf>,
+}
+
+/// shows what went wrong in the window until the player closes it, rather
+/// than panicking to a console they may never see
+pub async fn show_startup_error(err: &StartupError) {
+    loop {
+        if is_key_pressed(KeyCode::Escape) || is_key_pressed(KeyCode::Enter) {
+            return;
+        }
+
+        clear_background(LIGHTGRAY);
+        draw_centered_text("Asteroids couldn't start", screen_height() / 4., 40.);
+
+        let mut y = screen_height() / 4. + 50.;
+        for line in wrap(&err.message, screen_width() - 80., 20) {
+            draw_centered_text(&line, y, 20.);
+            y += 24.;
+        }
+        if let Some(path) = &err.path {
+            y += 12.;
+            draw_centered_text("The problem is in this file:", y, 18.);
+            for line in wrap(&path.display().to_string(), screen_width() - 80., 18) {
+                y += 22.;
+                draw_text(&line, 40., y, 18., DARKBLUE);
+            }
+        }
+
+        draw_centered_text("Press enter or escape to quit", screen_height() - 40., 18.);
+        next_frame().await;
+    }
+}
+
+/// breaks `text` into lines no wider than `width` at the given font size,
+/// splitting at spaces where it can
+fn wrap(text: &str, width: f32, font_size: u16) -> Vec<String> {
+    let fits = |line: &str| measure_text(line, None, font_size, 1.0).width <= width;
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split(' ') {
+        let candidate = if line.is_empty() {
+            word.to_string()
+        } else {
+            format!("{} {}", line, word)
+        };
+        if fits(&candidate) || line.is_empty() {
+            line = candidate;
+        } else {
+            lines.push(std::mem::replace(&mut line, word.to_string()));
+        }
+    }
+    lines.push(line);
+    lines
+}
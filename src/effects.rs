@@ -0,0 +1,92 @@
+//! short-lived effects that are only there to look at
+
+use crate::angle::Angle;
+use crate::collision::Hit;
+use crate::particles::Particles;
+use crate::physics::Point;
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub const CRIT_FLASH_TIME: f64 = 0.3;
+pub const FLOATING_TEXT_TIME: f64 = 0.8;
+pub const SHOCKWAVE_TIME: f64 = 0.4;
+pub const SHIELD_RIPPLE_TIME: f64 = 0.5;
+pub const DAMAGE_FLASH_TIME: f64 = 0.07;
+
+/// makes an entity flash white for a moment after it takes damage and
+/// survives. Entities hold one and the renderer checks it when drawing them.
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+pub struct DamageFlash {
+    /// when the flash ends, by the world's clock
+    #[serde(default)]
+    pub until: f64,
+}
+impl DamageFlash {
+    pub fn trigger(&mut self, now: f64) {
+        self.until = now + DAMAGE_FLASH_TIME;
+    }
+
+    pub fn is_flashing(&self, now: f64) -> bool {
+        now < self.until
+    }
+}
+
+/// a burst drawn where a critical hit landed
+pub struct CritFlash {
+    pub pos: Point,
+    pub start_time: f64,
+}
+
+/// a ring spreading out from where a smart bomb went off
+pub struct Shockwave {
+    pub pos: Point,
+    /// how far the ring spreads before it's gone
+    pub radius: f32,
+    pub start_time: f64,
+}
+
+/// a ripple on the ship's shield where it took a hit. It follows the ship,
+/// so only the side it was hit from is kept.
+pub struct ShieldRipple {
+    /// which way the hit came from, seen from the ship
+    pub angle: Angle,
+    pub start_time: f64,
+}
+
+/// a short message that drifts upward and fades out where something happened
+pub struct FloatingText {
+    pub text: String,
+    pub pos: Point,
+    pub color: Color,
+    pub start_time: f64,
+}
+
+/// picks the color of a damage number by what dealt the damage
+pub fn damage_color(hit: &Hit) -> Color {
+    if hit.critical {
+        ORANGE
+    } else {
+        DARKBLUE
+    }
+}
+
+/// the effects currently playing out
+#[derive(Default)]
+pub struct Effects {
+    pub crit_flashes: Vec<CritFlash>,
+    pub floating_texts: Vec<FloatingText>,
+    pub shockwaves: Vec<Shockwave>,
+    pub shield_ripples: Vec<ShieldRipple>,
+    pub particles: Particles,
+}
+impl Effects {
+    /// drops any effects that have finished
+    pub fn expire(&mut self, now: f64) {
+        self.crit_flashes.retain(|flash| flash.start_time + CRIT_FLASH_TIME > now);
+        self.floating_texts.retain(|text| text.start_time + FLOATING_TEXT_TIME > now);
+        self.shockwaves.retain(|wave| wave.start_time + SHOCKWAVE_TIME > now);
+        self.shield_ripples
+            .retain(|ripple| ripple.start_time + SHIELD_RIPPLE_TIME > now);
+        self.particles.expire(now);
+    }
+}
@@ -0,0 +1,101 @@
+//! notable things that happen in a game, and the feed that lists them
+
+use crate::asteroid::Material;
+use crate::powerup::PowerUpKind;
+use crate::satellite::SATELLITE_CRIT_BONUS;
+use crate::saucer::SaucerKind;
+use crate::ship::Direction;
+
+pub const FEED_ENTRY_TIME: f64 = 3.;
+pub const FEED_MAX_ENTRIES: usize = 5;
+
+/// something notable that happened in the game. Systems push events as they
+/// happen, and anything interested in them reads the list before it is
+/// cleared at the end of the frame.
+#[derive(Debug, Clone, Copy)]
+pub enum GameEvent {
+    ShotFired,
+    AsteroidsIncoming(usize),
+    AsteroidCracked,
+    /// an ice shell broke off an asteroid with this many sides
+    ShellBroken { sides: u8 },
+    /// the last layer of an asteroid with this many sides broke
+    AsteroidDestroyed { sides: u8, material: Material },
+    SatelliteCollected,
+    PowerUpCollected(PowerUpKind),
+    /// the ship's shield took a hit and is gone
+    ShieldAbsorbed,
+    CriticalHit,
+    /// the score passed another bonus life threshold
+    ExtraLife,
+    GameSaved,
+    GameLoaded,
+    SaucerArrived,
+    /// a saucer of this kind was shot down or rammed
+    SaucerDestroyed(SaucerKind),
+    /// this wave was cleared after this many seconds of play, earning this
+    /// speed bonus
+    WaveCleared { wave: u32, secs: f64, bonus: u32 },
+    /// the field was cleared and this wave is on its way in
+    WaveStarted(u32),
+    /// the ship was hit by an asteroid with this many sides, coming from
+    /// this direction relative to where the ship was facing
+    ShipDestroyed { sides: u8, from: Direction },
+    /// the ship was hit by a saucer or one of its shots, coming from this
+    /// direction relative to where the ship was facing
+    ShipDestroyedBySaucer { from: Direction },
+    /// the ship was hit by an asteroid turret's shot, coming from this
+    /// direction relative to where the ship was facing
+    ShipDestroyedByTurret { from: Direction },
+}
+impl GameEvent {
+    /// a short line for the event feed, if the event is worth showing there
+    pub fn describe(&self) -> Option<String> {
+        match self {
+            GameEvent::ShotFired => None,
+            GameEvent::AsteroidsIncoming(count) => Some(format!("{} asteroids incoming", count)),
+            GameEvent::AsteroidCracked => Some("Asteroid cracked".to_string()),
+            GameEvent::ShellBroken { .. } => Some("Ice shell shattered".to_string()),
+            GameEvent::SatelliteCollected => Some(format!(
+                "Satellite collected: +{:.0}% critical chance",
+                SATELLITE_CRIT_BONUS * 100.
+            )),
+            GameEvent::PowerUpCollected(kind) => Some(format!("{} collected", kind.name())),
+            GameEvent::ShieldAbsorbed => Some("Shield absorbed a hit".to_string()),
+            GameEvent::AsteroidDestroyed { .. } => Some("Asteroid destroyed".to_string()),
+            GameEvent::CriticalHit => Some("Critical hit!".to_string()),
+            GameEvent::ExtraLife => Some("Extra life!".to_string()),
+            GameEvent::GameSaved => Some("Game saved".to_string()),
+            GameEvent::GameLoaded => Some("Game loaded".to_string()),
+            GameEvent::WaveCleared { wave, secs, bonus: 0 } => {
+                Some(format!("Wave {} cleared in {:.0}s", wave, secs))
+            }
+            GameEvent::WaveCleared { wave, secs, bonus } => Some(format!(
+                "Wave {} cleared in {:.0}s: speed bonus +{}",
+                wave, secs, bonus
+            )),
+            // the wave banner already says so
+            GameEvent::WaveStarted(_) => None,
+            GameEvent::SaucerArrived => Some("Saucer incoming".to_string()),
+            GameEvent::SaucerDestroyed(SaucerKind::Large) => Some("Saucer destroyed".to_string()),
+            GameEvent::SaucerDestroyed(SaucerKind::Small) => Some("Small saucer destroyed".to_string()),
+            GameEvent::ShipDestroyed { .. }
+            | GameEvent::ShipDestroyedBySaucer { .. }
+            | GameEvent::ShipDestroyedByTurret { .. } => Some("Ship destroyed".to_string()),
+        }
+    }
+
+    /// whether the event means a bullet struck an asteroid
+    pub fn is_hit(&self) -> bool {
+        matches!(
+            self,
+            GameEvent::AsteroidCracked | GameEvent::ShellBroken { .. } | GameEvent::AsteroidDestroyed { .. }
+        )
+    }
+}
+
+/// a line in the event feed, which fades out after a few seconds
+pub struct FeedEntry {
+    pub text: String,
+    pub start_time: f64,
+}
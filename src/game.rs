@@ -0,0 +1,580 @@
+//! a game being played: the world plus everything that watches it
+
+use crate::audio::Audio;
+use crate::balance::Balance;
+use crate::background::{BackgroundAsteroid, BACKGROUND_ASTEROIDS};
+use crate::bullet::Projectile;
+use crate::collision::check_collisions;
+use crate::effects::Effects;
+use crate::events::{FeedEntry, GameEvent, FEED_ENTRY_TIME, FEED_MAX_ENTRIES};
+use crate::input::{write_bug_report, Gamepads, InputFrame, InputMap, Recording, WeaponChoice};
+use crate::physics::{frame_delta, Point, TICK};
+use crate::rainbow::Rainbow;
+use crate::random;
+use crate::render::{self, draw_centered_text, Layer, RenderQueue};
+use crate::report::RunReport;
+use crate::scoring;
+use crate::secrets::{Secret, Secrets};
+use crate::settings::{Edges, Rules, Settings};
+use crate::share::ShareCode;
+use crate::state::GameState;
+use crate::storage::Storage;
+use crate::world::{Snapshot, World, WAVE_BANNER_TIME};
+use macroquad::prelude::*;
+use std::collections::VecDeque;
+
+const QUICKSAVE_FILE: &str = "quicksave.json";
+/// how far ahead practice runs show the asteroids' paths, in seconds
+const TRAJECTORY_SECS: f32 = 3.;
+/// how far ahead the flight path assist looks, in seconds
+const FLIGHT_PATH_SECS: f32 = 2.;
+/// seconds the lives flash for after a bonus life
+const EXTRA_LIFE_FLASH_TIME: f64 = 1.5;
+/// ships the extra lives cheat gives
+const CHEAT_LIVES: u8 = 3;
+
+pub struct Game {
+    pub settings: Settings,
+    pub rules: Rules,
+    pub world: World,
+    pub report: RunReport,
+    /// where saves, reports and the like are kept
+    pub storage: Box<dyn Storage>,
+    pub audio: Audio,
+    effects: Effects,
+    events: Vec<GameEvent>,
+    event_feed: Vec<FeedEntry>,
+    /// the last minute or two of play, for bug reports: the one before the
+    /// latest, if there is one, and the one still being recorded
+    recordings: VecDeque<Recording>,
+    input_map: InputMap,
+    gamepads: Gamepads,
+    background: Vec<BackgroundAsteroid>,
+    /// the secret codes, and the keys typed lately to spot them in
+    secrets: Secrets,
+    /// the tunable numbers from the balance file
+    balance: Balance,
+    /// the rainbow easter egg, while it's on
+    rainbow: Option<Rainbow>,
+    /// whether the outlines of the shapes that collide are drawn, from a
+    /// cheat code
+    show_hitboxes: bool,
+    danger: f32,
+    /// when the last bonus life was awarded, for flashing the lives
+    extra_life_time: f64,
+    /// frame time that hasn't been stepped through yet, always less than a
+    /// tick after an update
+    unsimulated: f32,
+    /// a switch of weapon pressed on a frame too short to step through
+    pending_weapon: Option<WeaponChoice>,
+}
+impl Game {
+    pub fn new(
+        settings: Settings,
+        mut audio: Audio,
+        input_map: InputMap,
+        secrets: Secrets,
+        balance: Balance,
+        storage: Box<dyn Storage>,
+    ) -> Game {
+        audio.apply_volumes(&settings);
+        let mut background: Vec<BackgroundAsteroid> = (0..BACKGROUND_ASTEROIDS)
+            .map(|_| BackgroundAsteroid::new())
+            .collect();
+        // draw the furthest silhouettes first
+        background.sort_by(|a, b| a.depth.total_cmp(&b.depth));
+
+        let seed = random::fresh_seed();
+        let world = World::new(settings.density, settings.difficulty, seed);
+        let rules = Rules::default();
+        let report = RunReport::new(ShareCode::new(seed, &settings, &rules).to_string());
+        Game {
+            world,
+            settings,
+            rules,
+            report,
+            storage,
+            audio,
+            effects: Effects::default(),
+            events: Vec::new(),
+            event_feed: Vec::new(),
+            recordings: VecDeque::new(),
+            input_map,
+            gamepads: Gamepads::new(),
+            background,
+            secrets,
+            balance,
+            rainbow: None,
+            show_hitboxes: false,
+            danger: 0.,
+            extra_life_time: f64::MIN,
+            unsimulated: 0.,
+            pending_weapon: None,
+        }
+    }
+
+    /// whether a gamepad was used since the last time one was read, for
+    /// telling when someone is back at the menus
+    pub fn poll_gamepads(&mut self) -> bool {
+        self.gamepads.poll()
+    }
+
+    /// starts a new run, keeping the settings and rules
+    pub fn restart(&mut self) {
+        self.restart_from(random::fresh_seed());
+    }
+
+    /// starts a new run rolled from `seed`, so it begins the same as any
+    /// other run from that seed with the same settings and rules
+    pub fn restart_from(&mut self, seed: u64) {
+        self.world = World::new(self.settings.density, self.settings.difficulty, seed);
+        self.effects = Effects::default();
+        self.event_feed = Vec::new();
+        let code = ShareCode::new(seed, &self.settings, &self.rules);
+        self.report = RunReport::new(code.to_string());
+        self.recordings = VecDeque::from([Recording::new(&self.world)]);
+        self.extra_life_time = f64::MIN;
+        self.show_hitboxes = false;
+        self.unsimulated = 0.;
+        self.events.push(GameEvent::AsteroidsIncoming(self.world.spawn_warnings.len()));
+    }
+
+    /// plays one frame, stepping the simulation as many whole ticks as the
+    /// frame took, and says whether the run goes on or ended with the last
+    /// ship destroyed
+    pub fn update(&mut self) -> GameState {
+        let toggles = [
+            (KeyCode::N, &mut self.settings.show_damage_numbers),
+            (KeyCode::L, &mut self.settings.show_event_feed),
+            (KeyCode::V, &mut self.settings.show_danger_vignette),
+            (KeyCode::T, &mut self.settings.show_trajectories),
+        ];
+        let mut toggled = false;
+        for (key, setting) in toggles {
+            if is_key_pressed(key) {
+                *setting = !*setting;
+                toggled = true;
+            }
+        }
+        if toggled {
+            if let Err(err) = self.save_settings() {
+                self.event_feed.push(FeedEntry {
+                    text: err,
+                    start_time: self.world.time,
+                });
+            }
+        }
+
+        if is_key_pressed(KeyCode::F10) {
+            self.save_bug_report();
+        }
+
+        // quicksave and quickload
+        if is_key_pressed(KeyCode::F5) {
+            match Snapshot::capture(&self.world).save(self.storage.as_ref(), QUICKSAVE_FILE) {
+                Ok(()) => self.events.push(GameEvent::GameSaved),
+                Err(err) => self.event_feed.push(FeedEntry {
+                    text: err,
+                    start_time: self.world.time,
+                }),
+            }
+        } else if is_key_pressed(KeyCode::F9) {
+            match Snapshot::load(self.storage.as_ref(), QUICKSAVE_FILE) {
+                Ok(snapshot) => {
+                    self.world = snapshot.restore();
+                    // the feed and effects run on the world's clock too
+                    self.effects = Effects::default();
+                    self.event_feed = Vec::new();
+                    self.extra_life_time = f64::MIN;
+                    // what was recorded before doesn't lead up to this world
+                    self.recordings = VecDeque::from([Recording::new(&self.world)]);
+                    // nor do the stats, and the score goes back to the saved one
+                    let code = ShareCode::new(self.world.seed, &self.settings, &self.rules);
+                    self.report = RunReport::resume(code.to_string(), &self.world);
+                    self.events.push(GameEvent::GameLoaded);
+                }
+                Err(err) => self.event_feed.push(FeedEntry {
+                    text: err,
+                    start_time: self.world.time,
+                }),
+            }
+        }
+
+        if let Some(rainbow) = self.rainbow.as_mut() {
+            rainbow.fit_screen();
+        }
+
+        let mut input = InputFrame::read(&self.input_map, &mut self.gamepads, self.settings.controls);
+        input.weapon = input.weapon.or(self.pending_weapon.take());
+        let delta = frame_delta();
+        self.drift_background(delta);
+        self.effects.particles.measure(get_frame_time());
+
+        self.unsimulated += delta;
+        let mut state = GameState::Playing;
+        while self.unsimulated >= TICK && state == GameState::Playing {
+            self.unsimulated -= TICK;
+            state = self.tick(input);
+            // a switch of weapon happens on one step only
+            input.weapon = None;
+        }
+        // or waits for the next frame's steps if there wasn't one this frame
+        self.pending_weapon = input.weapon;
+        state
+    }
+
+    /// steps the simulation forward by one tick with the controls held this
+    /// frame
+    fn tick(&mut self, mut input: InputFrame) -> GameState {
+        input.time = self.world.time - self.world.run_start;
+        if self.recordings.back().is_none_or(|recording| recording.is_full(self.world.time)) {
+            self.recordings.push_back(Recording::new(&self.world));
+            // the one before covers at least the last minute on its own
+            if self.recordings.len() > 2 {
+                self.recordings.pop_front();
+            }
+        }
+        if let Some(recording) = self.recordings.back_mut() {
+            recording.inputs.push(input);
+        }
+
+        self.world.update(
+            &input,
+            self.rules.edges,
+            self.settings.asteroids_bounce,
+            TICK,
+            &mut self.events,
+        );
+        self.effects.particles.advance(TICK);
+        let now = self.world.time;
+        for bullet in self.world.bullets.iter() {
+            if bullet.projectile == Projectile::Missile {
+                self.effects.particles.smoke(bullet.pos, now);
+            }
+        }
+
+        let ship_hit = check_collisions(
+            &mut self.world,
+            &mut self.effects,
+            &mut self.events,
+            self.settings.show_damage_numbers,
+            now,
+        );
+        self.world.remove_spent();
+        self.effects.expire(now);
+
+        // hand this tick's events to the feed and the run report
+        let score_before = self.world.score;
+        self.world.add_points(&self.events);
+        for event in self.events.drain(..) {
+            let sound = match event {
+                GameEvent::CriticalHit => self.audio.crit,
+                GameEvent::ExtraLife => {
+                    self.extra_life_time = now;
+                    self.audio.extra_life
+                }
+                _ => None,
+            };
+            if let Some(sound) = sound {
+                self.audio.effects.play_once(sound);
+            }
+            self.report.record(now - self.world.run_start, &event);
+            if let Some(text) = event.describe() {
+                self.event_feed.push(FeedEntry {
+                    text,
+                    start_time: now,
+                });
+            }
+        }
+        self.report.duration_secs = now - self.world.run_start;
+        self.report.score = self.world.score;
+        if let Some(tone) = self.audio.heartbeat.update(self.world.lives, now) {
+            self.audio.effects.play_once(tone);
+        }
+
+        // the announcement goes out with next tick's events
+        let every = self.balance.extra_life_points;
+        for _ in 0..scoring::extra_lives_earned(score_before, self.world.score, every) {
+            self.world.lives = self.world.lives.saturating_add(1);
+            self.events.push(GameEvent::ExtraLife);
+        }
+        self.event_feed.retain(|entry| entry.start_time + FEED_ENTRY_TIME > now);
+        if self.event_feed.len() > FEED_MAX_ENTRIES {
+            self.event_feed.drain(..self.event_feed.len() - FEED_MAX_ENTRIES);
+        }
+
+        // ease toward the current danger so the vignette doesn't flicker
+        let danger = render::danger_level(&self.world.ship, &self.world.asteroids, self.world.lives);
+        self.danger += (danger - self.danger) * 0.05;
+
+        if self.world.is_cleared() {
+            let secs = now - self.world.wave_start;
+            self.events.push(GameEvent::WaveCleared {
+                wave: self.world.wave,
+                secs,
+                bonus: scoring::speed_bonus(secs, self.world.wave_par),
+            });
+            self.world.next_wave(self.settings.density);
+            self.events.push(GameEvent::WaveStarted(self.world.wave));
+            self.events.push(GameEvent::AsteroidsIncoming(self.world.spawn_warnings.len()));
+        }
+
+        if ship_hit && !self.world.lose_life(self.rules.practice) {
+            GameState::GameOver
+        } else {
+            GameState::Playing
+        }
+    }
+
+    pub fn save_settings(&self) -> Result<(), String> {
+        self.settings.save(self.storage.as_ref())
+    }
+
+    /// writes the recorded inputs and the snapshot they start from to a zip
+    /// file and says in the feed where it went
+    fn save_bug_report(&mut self) {
+        let seconds = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs());
+        let name = format!("bug_report_{}.zip", seconds);
+        let storage = self.storage.as_ref();
+        let written = write_bug_report(storage, &name, &self.recordings, &self.settings, &self.rules);
+        let text = match written {
+            Ok(()) => format!(
+                "Bug report saved to {}. Please attach it to an issue.",
+                storage.path(&name).display()
+            ),
+            Err(err) => format!("Couldn't save a bug report: {}", err),
+        };
+        self.event_feed.push(FeedEntry {
+            text,
+            start_time: self.world.time,
+        });
+    }
+
+    /// moves the background silhouettes along by `delta` seconds
+    pub fn drift_background(&mut self, delta: f32) {
+        for silhouette in self.background.iter_mut() {
+            silhouette.advance(delta);
+        }
+    }
+
+    /// draws the background silhouettes on their own, as though the ship
+    /// sat in the middle of the screen
+    pub fn draw_background(&self) {
+        let center = Point {
+            x: screen_width() / 2.,
+            y: screen_height() / 2.,
+        };
+        for silhouette in self.background.iter() {
+            silhouette.draw(center);
+        }
+    }
+
+    /// checks the keys typed for secret codes. This is only done while the
+    /// game is paused, where none of the keys in the codes do anything else.
+    /// Cheats mark the run so it can't make the high score table.
+    pub fn watch_for_secrets(&mut self) {
+        let Some(secret) = self.secrets.update() else {
+            return;
+        };
+        if secret.is_cheat() {
+            self.world.cheated = true;
+        }
+        let text = match secret {
+            Secret::Rainbow => {
+                self.toggle_rainbow();
+                return;
+            }
+            Secret::ExtraLives => {
+                self.world.lives = self.world.lives.saturating_add(CHEAT_LIVES);
+                format!("Cheat: +{} ships", CHEAT_LIVES)
+            }
+            Secret::Hitboxes => {
+                self.show_hitboxes = !self.show_hitboxes;
+                "Cheat: hitboxes".to_string()
+            }
+        };
+        self.event_feed.push(FeedEntry {
+            text,
+            start_time: self.world.time,
+        });
+    }
+
+    /// turns rainbow mode on or off
+    fn toggle_rainbow(&mut self) {
+        self.rainbow = match self.rainbow.take() {
+            Some(_) => None,
+            None => match Rainbow::new() {
+                Ok(rainbow) => Some(rainbow),
+                Err(err) => {
+                    self.event_feed.push(FeedEntry {
+                        text: err,
+                        start_time: self.world.time,
+                    });
+                    None
+                }
+            },
+        };
+    }
+
+    /// draws the world part way between the last tick and the next, by how
+    /// much of a tick has built up since, through rainbow mode if it's on
+    pub fn draw(&self) {
+        match self.rainbow.as_ref() {
+            Some(rainbow) => rainbow.draw(get_time(), || self.draw_world()),
+            None => {
+                clear_background(LIGHTGRAY);
+                self.draw_world();
+            }
+        }
+    }
+
+    fn draw_world(&self) {
+        let world = &self.world.interpolated(self.unsimulated / TICK);
+        let now = self.world.time;
+
+        let mut render = RenderQueue::default();
+
+        render.register(Layer::Background, || {
+            for silhouette in self.background.iter() {
+                silhouette.draw(world.ship.pos);
+            }
+        });
+
+        if self.rules.edges == Edges::Walls {
+            render.register(Layer::World, || {
+                draw_rectangle_lines(0., 0., screen_width(), screen_height(), 6., DARKGRAY);
+            });
+        }
+
+        render.register(Layer::World, || {
+            for bullet in world.bullets.iter() {
+                if bullet.projectile == Projectile::Missile {
+                    render::draw_missile(bullet);
+                } else {
+                    draw_circle(bullet.pos.x, bullet.pos.y, 2., BLACK);
+                }
+            }
+            for shot in world.enemy_shots.iter() {
+                render::draw_enemy_shot(shot);
+            }
+            if let Some(saucer) = world.saucer.as_ref() {
+                render::draw_saucer(saucer);
+            }
+        });
+
+        render.register(Layer::World, || {
+            for asteroid in world.asteroids.iter() {
+                render::draw_asteroid(asteroid, now);
+                if let Some(turret) = asteroid.turret.as_ref() {
+                    render::draw_turret(asteroid.pos, turret);
+                }
+            }
+        });
+
+        render.register(Layer::World, || {
+            for satellite in world.asteroids.iter().flat_map(|asteroid| asteroid.satellites.iter()) {
+                render::draw_satellite(satellite);
+            }
+            for satellite in world.satellites.iter() {
+                render::draw_loose_satellite(satellite, now);
+            }
+            for powerup in world.powerups.iter() {
+                render::draw_powerup(powerup, now);
+            }
+        });
+
+        // a freshly respawned ship blinks while nothing can hit it
+        if !world.ship.is_invulnerable(now) || (now * 8.) as i64 % 2 == 0 {
+            render.register(Layer::World, || {
+                render::draw_laser(&world.ship);
+                render::draw_exhaust(&world.ship, now);
+                render::draw_ship(&world.ship, now);
+                render::draw_shield(&world.ship);
+            });
+        }
+
+        render.register(Layer::Hud, || {
+            render::draw_lives(
+                world.lives,
+                now - self.extra_life_time < EXTRA_LIFE_FLASH_TIME,
+                self.audio.heartbeat.pulse(now),
+            );
+            render::draw_wave_counter(world.wave);
+            render::draw_energy(&world.ship);
+            render::draw_bombs(world.ship.bombs);
+            render::draw_powerup_timers(&world.ship, now);
+            render::draw_weapon(&world.ship);
+            render::draw_score(self.report.score);
+            if self.rules.practice {
+                draw_centered_text("Practice", 52., 18.);
+            } else if world.cheated {
+                draw_centered_text("Cheats used", 52., 18.);
+            }
+        });
+
+        if self.rules.practice && self.settings.show_trajectories {
+            render.register(Layer::Effects, || {
+                for path in self.world.predicted_paths(self.rules.edges, TRAJECTORY_SECS) {
+                    render::draw_predicted_path(&path, DARKGRAY);
+                }
+            });
+        }
+        if self.show_hitboxes {
+            render.register(Layer::Effects, || {
+                for asteroid in world.asteroids.iter() {
+                    render::draw_outline(&asteroid.outline(), RED);
+                }
+                render::draw_outline(&world.ship.outline(), RED);
+            });
+        }
+        if self.settings.show_flight_path {
+            render.register(Layer::Effects, || {
+                let (coasting, thrusting) =
+                    self.world.predicted_ship_paths(self.rules.edges, FLIGHT_PATH_SECS);
+                render::draw_predicted_path(&coasting, DARKGRAY);
+                render::draw_predicted_path(&thrusting, ORANGE);
+            });
+        }
+        if now - world.wave_start < WAVE_BANNER_TIME {
+            render.register(Layer::Overlay, || render::draw_wave_banner(world.wave));
+        }
+
+        render.register(Layer::Effects, || {
+            self.effects.particles.draw(now);
+            for wave in self.effects.shockwaves.iter() {
+                render::draw_shockwave(wave, now);
+            }
+            for ripple in self.effects.shield_ripples.iter() {
+                render::draw_shield_ripple(ripple, &world.ship, now);
+            }
+            for flash in self.effects.crit_flashes.iter() {
+                render::draw_crit_flash(flash, now);
+            }
+            for floating in self.effects.floating_texts.iter() {
+                render::draw_floating_text(floating, now);
+            }
+        });
+
+        if self.settings.show_event_feed {
+            render.register(Layer::Hud, || render::draw_event_feed(&self.event_feed, now));
+        }
+
+        if self.settings.show_danger_vignette {
+            render.register(Layer::Overlay, || render::draw_danger_vignette(self.danger));
+        }
+
+        // spawn warnings are never hidden behind anything else
+        render.register(Layer::Overlay, || {
+            for warning in world.spawn_warnings.iter() {
+                render::draw_spawn_warning(warning);
+            }
+            if let Some(warning) = world.saucer_warning.as_ref() {
+                render::draw_saucer_warning(warning);
+            }
+        });
+
+        render.draw();
+    }
+}
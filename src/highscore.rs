@@ -0,0 +1,52 @@
+//! the best scores so far, kept on disk between runs
+
+use crate::crash::StartupError;
+use crate::migrate::HIGH_SCORE_MIGRATIONS;
+use crate::storage::{self, Storage};
+use serde::{Deserialize, Serialize};
+
+const MAX_HIGH_SCORES: usize = 10;
+pub const MAX_INITIALS: usize = 3;
+const HIGH_SCORES_FILE: &str = "high_scores.json";
+
+/// one finished run on the table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HighScore {
+    pub score: u32,
+    /// up to three letters, or empty if the player skipped them
+    #[serde(default)]
+    pub initials: String,
+    pub wave: u32,
+}
+
+/// the top scores, best first
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct HighScores {
+    pub entries: Vec<HighScore>,
+}
+impl HighScores {
+    /// the table saved last time, or an empty one if there isn't one yet.
+    /// See `load_versioned` for what happens to old or broken files.
+    pub fn load(storage: &dyn Storage) -> Result<(HighScores, Option<String>), StartupError> {
+        storage::load_versioned(storage, HIGH_SCORES_FILE, HIGH_SCORE_MIGRATIONS, "the high scores")
+    }
+
+    pub fn save(&self, storage: &dyn Storage) -> Result<(), String> {
+        storage::save_versioned(storage, HIGH_SCORES_FILE, self, HIGH_SCORE_MIGRATIONS)
+    }
+
+    /// whether a run with this score earns a place on the table
+    pub fn qualifies(&self, score: u32) -> bool {
+        score > 0
+            && (self.entries.len() < MAX_HIGH_SCORES
+                || self.entries.last().is_some_and(|lowest| score > lowest.score))
+    }
+
+    /// adds a run to the table, below any earlier runs with the same score,
+    /// dropping whatever falls off the bottom
+    pub fn insert(&mut self, entry: HighScore) {
+        let place = self.entries.partition_point(|other| other.score >= entry.score);
+        self.entries.insert(place, entry);
+        self.entries.truncate(MAX_HIGH_SCORES);
+    }
+}
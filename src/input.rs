@@ -0,0 +1,414 @@
+//! the controls the player is holding, and reports built from them
+
+use crate::physics::Point;
+use crate::settings::{Controls, Rules, Settings};
+use crate::storage::Storage;
+use crate::weapon::Weapon;
+use crate::world::{Snapshot, World};
+use gilrs::{Axis, Button, EventType, GamepadId, Gilrs};
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::io::Write;
+
+/// seconds of play each recording covers before a new one starts
+pub const RECORDING_SECS: f64 = 60.;
+/// how far a stick has to be pushed before it counts
+const STICK_DEAD_ZONE: f32 = 0.4;
+const CONTROLS_FILE: &str = "controls.toml";
+/// the keys that pick each of `Weapon::ALL`
+const WEAPON_KEYS: [KeyCode; 4] = [KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4];
+
+/// the names keys go by in the controls file
+const KEY_NAMES: &[(&str, KeyCode)] = &[
+    ("Up", KeyCode::Up),
+    ("Down", KeyCode::Down),
+    ("Left", KeyCode::Left),
+    ("Right", KeyCode::Right),
+    ("Space", KeyCode::Space),
+    ("Enter", KeyCode::Enter),
+    ("Tab", KeyCode::Tab),
+    ("LeftShift", KeyCode::LeftShift),
+    ("RightShift", KeyCode::RightShift),
+    ("LeftControl", KeyCode::LeftControl),
+    ("RightControl", KeyCode::RightControl),
+    ("LeftAlt", KeyCode::LeftAlt),
+    ("RightAlt", KeyCode::RightAlt),
+    ("A", KeyCode::A),
+    ("B", KeyCode::B),
+    ("C", KeyCode::C),
+    ("D", KeyCode::D),
+    ("E", KeyCode::E),
+    ("F", KeyCode::F),
+    ("G", KeyCode::G),
+    ("H", KeyCode::H),
+    ("I", KeyCode::I),
+    ("J", KeyCode::J),
+    ("K", KeyCode::K),
+    ("L", KeyCode::L),
+    ("M", KeyCode::M),
+    ("N", KeyCode::N),
+    ("O", KeyCode::O),
+    ("P", KeyCode::P),
+    ("Q", KeyCode::Q),
+    ("R", KeyCode::R),
+    ("S", KeyCode::S),
+    ("T", KeyCode::T),
+    ("U", KeyCode::U),
+    ("V", KeyCode::V),
+    ("W", KeyCode::W),
+    ("X", KeyCode::X),
+    ("Y", KeyCode::Y),
+    ("Z", KeyCode::Z),
+    ("0", KeyCode::Key0),
+    ("1", KeyCode::Key1),
+    ("2", KeyCode::Key2),
+    ("3", KeyCode::Key3),
+    ("4", KeyCode::Key4),
+    ("5", KeyCode::Key5),
+    ("6", KeyCode::Key6),
+    ("7", KeyCode::Key7),
+    ("8", KeyCode::Key8),
+    ("9", KeyCode::Key9),
+    ("Kp0", KeyCode::Kp0),
+    ("Kp1", KeyCode::Kp1),
+    ("Kp2", KeyCode::Kp2),
+    ("Kp3", KeyCode::Kp3),
+    ("Kp4", KeyCode::Kp4),
+    ("Kp5", KeyCode::Kp5),
+    ("Kp6", KeyCode::Kp6),
+    ("Kp7", KeyCode::Kp7),
+    ("Kp8", KeyCode::Kp8),
+    ("Kp9", KeyCode::Kp9),
+];
+
+/// which keys do what in play. Any of an action's keys will do it.
+pub struct InputMap {
+    pub thrust: Vec<KeyCode>,
+    pub left: Vec<KeyCode>,
+    pub right: Vec<KeyCode>,
+    pub fire: Vec<KeyCode>,
+    pub hyperspace: Vec<KeyCode>,
+    pub bomb: Vec<KeyCode>,
+    pub next_weapon: Vec<KeyCode>,
+}
+impl Default for InputMap {
+    fn default() -> InputMap {
+        InputMap {
+            thrust: vec![KeyCode::Up],
+            left: vec![KeyCode::Left],
+            right: vec![KeyCode::Right],
+            fire: vec![KeyCode::Space],
+            hyperspace: vec![KeyCode::LeftShift],
+            bomb: vec![KeyCode::B],
+            next_weapon: vec![KeyCode::Q],
+        }
+    }
+}
+impl InputMap {
+    /// the bindings in the controls file, with the defaults for any action
+    /// it leaves out or only gives unknown keys for. A missing file is
+    /// written out with the defaults so there's something to edit. Returns
+    /// a notice for the player if the file couldn't be used.
+    pub fn load(storage: &dyn Storage) -> (InputMap, Option<String>) {
+        /// the controls file as written, with keys by name
+        #[derive(Default, Serialize, Deserialize)]
+        #[serde(default)]
+        struct Bindings {
+            thrust: Vec<String>,
+            left: Vec<String>,
+            right: Vec<String>,
+            fire: Vec<String>,
+            hyperspace: Vec<String>,
+            bomb: Vec<String>,
+            next_weapon: Vec<String>,
+        }
+
+        let defaults = InputMap::default();
+        let text = match storage.read(CONTROLS_FILE) {
+            Ok(Some(text)) => text,
+            Ok(None) => {
+                let names = |keys: &[KeyCode]| keys.iter().filter_map(|key| key_name(*key)).collect();
+                let bindings = Bindings {
+                    thrust: names(&defaults.thrust),
+                    left: names(&defaults.left),
+                    right: names(&defaults.right),
+                    fire: names(&defaults.fire),
+                    hyperspace: names(&defaults.hyperspace),
+                    bomb: names(&defaults.bomb),
+                    next_weapon: names(&defaults.next_weapon),
+                };
+                // if this fails the defaults still work, and it's tried
+                // again next time
+                if let Ok(text) = toml::to_string(&bindings) {
+                    let _ = storage.write(CONTROLS_FILE, text.as_bytes());
+                }
+                return (defaults, None);
+            }
+            Err(err) => return (defaults, Some(format!("{}. Using the default controls.", err))),
+        };
+        let bindings: Bindings = match toml::from_str(&text) {
+            Ok(bindings) => bindings,
+            Err(err) => {
+                let notice = format!(
+                    "{} isn't valid, so the default controls are in use: {}",
+                    storage.path(CONTROLS_FILE).display(),
+                    err.message()
+                );
+                return (defaults, Some(notice));
+            }
+        };
+
+        let mut unknown = Vec::new();
+        let mut keys = |names: &[String], default: Vec<KeyCode>| {
+            let keys: Vec<KeyCode> = names
+                .iter()
+                .filter_map(|name| {
+                    let key = key_from_name(name);
+                    if key.is_none() {
+                        unknown.push(name.clone());
+                    }
+                    key
+                })
+                .collect();
+            if keys.is_empty() {
+                default
+            } else {
+                keys
+            }
+        };
+        let map = InputMap {
+            thrust: keys(&bindings.thrust, defaults.thrust),
+            left: keys(&bindings.left, defaults.left),
+            right: keys(&bindings.right, defaults.right),
+            fire: keys(&bindings.fire, defaults.fire),
+            hyperspace: keys(&bindings.hyperspace, defaults.hyperspace),
+            bomb: keys(&bindings.bomb, defaults.bomb),
+            next_weapon: keys(&bindings.next_weapon, defaults.next_weapon),
+        };
+        let notice = (!unknown.is_empty()).then(|| {
+            format!(
+                "Unknown keys in {}: {}",
+                storage.path(CONTROLS_FILE).display(),
+                unknown.join(", ")
+            )
+        });
+        (map, notice)
+    }
+
+    /// whether one of the keys to switch to the next weapon was pressed
+    /// this frame
+    pub fn next_weapon_pressed(&self) -> bool {
+        self.next_weapon.iter().any(|key| is_key_pressed(*key))
+    }
+}
+
+/// whether any of `keys` is held
+fn any_down(keys: &[KeyCode]) -> bool {
+    keys.iter().any(|key| is_key_down(*key))
+}
+
+pub fn key_from_name(name: &str) -> Option<KeyCode> {
+    KEY_NAMES
+        .iter()
+        .find(|(key_name, _)| key_name.eq_ignore_ascii_case(name))
+        .map(|(_, key)| *key)
+}
+
+pub fn key_name(key: KeyCode) -> Option<String> {
+    KEY_NAMES
+        .iter()
+        .find(|(_, named)| *named == key)
+        .map(|(name, _)| name.to_string())
+}
+
+/// the controls held down on one frame of play, from the keyboard, mouse
+/// and gamepad together
+#[derive(Debug, Default, Clone, Copy, Serialize)]
+pub struct InputFrame {
+    /// seconds since the run started, filled in when the controls are
+    /// applied to a simulation step
+    pub time: f64,
+    pub thrust: bool,
+    pub left: bool,
+    pub right: bool,
+    pub fire: bool,
+    pub hyperspace: bool,
+    pub bomb: bool,
+    /// where the mouse is pointing, while the right button is held to aim
+    pub aim: Option<Point>,
+    /// a weapon switch asked for with a key press this frame
+    pub weapon: Option<WeaponChoice>,
+}
+impl InputFrame {
+    pub fn read(map: &InputMap, gamepads: &mut Gamepads, controls: Controls) -> InputFrame {
+        let pad = gamepads.read();
+        InputFrame {
+            time: 0.,
+            thrust: any_down(&map.thrust) || pad.thrust,
+            left: any_down(&map.left) || pad.left,
+            right: any_down(&map.right) || pad.right,
+            fire: any_down(&map.fire) || pad.fire,
+            hyperspace: any_down(&map.hyperspace) || pad.hyperspace,
+            bomb: any_down(&map.bomb) || pad.bomb,
+            aim: (controls == Controls::MouseAim && is_mouse_button_down(MouseButton::Right)).then(|| {
+                let (x, y) = mouse_position();
+                Point { x, y }
+            }),
+            weapon: WeaponChoice::read(map),
+        }
+    }
+}
+
+/// a switch of weapon, which happens once when its key is pressed rather
+/// than for as long as it's held
+#[derive(Debug, Clone, Copy, Serialize)]
+pub enum WeaponChoice {
+    /// on to the next weapon that has shots left
+    Next,
+    Pick(Weapon),
+}
+impl WeaponChoice {
+    fn read(map: &InputMap) -> Option<WeaponChoice> {
+        if map.next_weapon_pressed() {
+            return Some(WeaponChoice::Next);
+        }
+        Weapon::ALL
+            .iter()
+            .zip(WEAPON_KEYS)
+            .find(|(_, key)| is_key_pressed(*key))
+            .map(|(weapon, _)| WeaponChoice::Pick(*weapon))
+    }
+}
+
+/// whichever gamepad was used last. Pads are picked up as they're plugged
+/// in or pressed, and the game plays on without one if there's no gamepad
+/// support on this system.
+pub struct Gamepads {
+    gilrs: Option<Gilrs>,
+    active: Option<GamepadId>,
+}
+impl Gamepads {
+    pub fn new() -> Gamepads {
+        let gilrs = Gilrs::new().ok();
+        let active = gilrs
+            .as_ref()
+            .and_then(|gilrs| gilrs.gamepads().next().map(|(id, _)| id));
+        Gamepads { gilrs, active }
+    }
+
+    /// takes in what has happened on the gamepads since last time, keeping
+    /// track of which one is active. Returns whether any of them was used:
+    /// a button pressed or a stick pushed past the dead zone.
+    pub fn poll(&mut self) -> bool {
+        let Some(gilrs) = self.gilrs.as_mut() else {
+            return false;
+        };
+        let mut used = false;
+        while let Some(event) = gilrs.next_event() {
+            match event.event {
+                EventType::Disconnected if self.active == Some(event.id) => self.active = None,
+                EventType::Disconnected => {}
+                _ => self.active = Some(event.id),
+            }
+            used |= match event.event {
+                EventType::ButtonPressed(..) => true,
+                EventType::AxisChanged(_, value, _) => value.abs() > STICK_DEAD_ZONE,
+                _ => false,
+            };
+        }
+        used
+    }
+
+    /// the controls held on the active gamepad: the left stick or d-pad to
+    /// turn and thrust, the bottom face button or right trigger to fire, and
+    /// the top face button to jump to hyperspace and the right face button
+    /// to set off a smart bomb
+    fn read(&mut self) -> InputFrame {
+        let mut frame = InputFrame {
+            time: 0.,
+            thrust: false,
+            left: false,
+            right: false,
+            fire: false,
+            hyperspace: false,
+            bomb: false,
+            aim: None,
+            weapon: None,
+        };
+        self.poll();
+        let Some(gilrs) = self.gilrs.as_ref() else {
+            return frame;
+        };
+        let Some(pad) = self.active.map(|id| gilrs.gamepad(id)) else {
+            return frame;
+        };
+
+        let stick_x = pad.value(Axis::LeftStickX);
+        let stick_y = pad.value(Axis::LeftStickY);
+        frame.left = stick_x < -STICK_DEAD_ZONE || pad.is_pressed(Button::DPadLeft);
+        frame.right = stick_x > STICK_DEAD_ZONE || pad.is_pressed(Button::DPadRight);
+        frame.thrust = stick_y > STICK_DEAD_ZONE || pad.is_pressed(Button::DPadUp);
+        frame.fire = pad.is_pressed(Button::South) || pad.is_pressed(Button::RightTrigger2);
+        frame.hyperspace = pad.is_pressed(Button::North);
+        frame.bomb = pad.is_pressed(Button::East);
+        frame
+    }
+}
+
+/// a snapshot of the game and the controls played on each step since it was
+/// taken, which together replay that stretch of the run exactly
+pub struct Recording {
+    pub start: Snapshot,
+    pub inputs: Vec<InputFrame>,
+}
+impl Recording {
+    pub fn new(world: &World) -> Recording {
+        Recording {
+            start: Snapshot::capture(world),
+            inputs: Vec::new(),
+        }
+    }
+
+    /// whether the recording covers long enough that a new one should start
+    pub fn is_full(&self, now: f64) -> bool {
+        now - self.start.time >= RECORDING_SECS
+    }
+}
+
+/// bundles the recorded inputs, the settings and rules, and the snapshot the
+/// inputs play on from into a zip file that can be attached to a bug report.
+/// The recordings run back to back, so the first one's snapshot starts them
+/// all.
+pub fn write_bug_report(
+    storage: &dyn Storage,
+    name: &str,
+    recordings: &VecDeque<Recording>,
+    settings: &Settings,
+    rules: &Rules,
+) -> Result<(), String> {
+    #[derive(Serialize)]
+    struct Options<'a> {
+        settings: &'a Settings,
+        rules: &'a Rules,
+    }
+
+    let first = recordings.front().ok_or("There's nothing recorded to report yet")?;
+    let inputs: Vec<&InputFrame> = recordings.iter().flat_map(|recording| &recording.inputs).collect();
+    let files = [
+        ("inputs.json", serde_json::to_string(&inputs)),
+        ("options.json", serde_json::to_string_pretty(&Options { settings, rules })),
+        ("snapshot.json", first.start.to_json()),
+    ];
+
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    for (name, contents) in files {
+        let contents = contents.map_err(|err| err.to_string())?;
+        zip.start_file(name, zip::write::FileOptions::default())
+            .map_err(|err| err.to_string())?;
+        zip.write_all(contents.as_bytes()).map_err(|err| err.to_string())?;
+    }
+    let zipped = zip.finish().map_err(|err| err.to_string())?;
+
+    storage.write(name, &zipped.into_inner())
+}
@@ -0,0 +1,535 @@
+//! the title screen, the pause and settings screens and the screen shown
+//! between runs
+
+use crate::audio::VOLUME_STEP;
+use crate::changelog::ChangelogScreen;
+use crate::crash::StartupError;
+use crate::game::Game;
+use crate::highscore::{HighScore, HighScores, MAX_INITIALS};
+use crate::render::draw_centered_text;
+use crate::report::{analyze_run, export_report};
+use crate::settings::Settings;
+use crate::share::{copy_to_clipboard, paste_from_clipboard, ShareCode};
+use crate::storage::Storage;
+use crate::widget::{Choice, OptionList};
+use macroquad::prelude::*;
+
+/// the entries on the settings screen, in order
+const SETTINGS_ENTRIES: [SettingsEntry; 12] = [
+    SettingsEntry::MusicVolume,
+    SettingsEntry::EffectsVolume,
+    SettingsEntry::Difficulty,
+    SettingsEntry::Density,
+    SettingsEntry::Controls,
+    SettingsEntry::AsteroidsBounce,
+    SettingsEntry::DamageNumbers,
+    SettingsEntry::EventFeed,
+    SettingsEntry::DangerVignette,
+    SettingsEntry::Trajectories,
+    SettingsEntry::FlightPath,
+    SettingsEntry::Back,
+];
+/// the entries on the title screen, in order
+const TITLE_ENTRIES: [&str; 5] = ["Play", "Play a Share Code", "High Scores", "Settings", "Quit"];
+/// the longest share code that can be typed in: the mode letters, a dash
+/// and a 64-bit seed in hex
+const MAX_SHARE_CODE_LEN: usize = 20;
+
+/// what the player picked on the title or game over screen
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MenuChoice {
+    Stay,
+    Play,
+    /// play a run from a share code's seed
+    PlaySeeded(u64),
+    Settings,
+    Quit,
+}
+
+/// one line of the settings screen
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SettingsEntry {
+    MusicVolume,
+    EffectsVolume,
+    Difficulty,
+    Density,
+    Controls,
+    AsteroidsBounce,
+    DamageNumbers,
+    EventFeed,
+    DangerVignette,
+    Trajectories,
+    FlightPath,
+    Back,
+}
+impl SettingsEntry {
+    /// the line as shown, with the setting's current value
+    fn label(&self, settings: &Settings) -> String {
+        let on_off = |on: bool| if on { "on" } else { "off" };
+        match self {
+            SettingsEntry::MusicVolume => format!("Music volume: {:.0}%", settings.music_volume * 100.),
+            SettingsEntry::EffectsVolume => format!("Effects volume: {:.0}%", settings.effects_volume * 100.),
+            SettingsEntry::Difficulty => format!("Difficulty: {}", settings.difficulty.name()),
+            SettingsEntry::Density => format!("Asteroid density: {}", settings.density.name()),
+            SettingsEntry::Controls => format!("Controls: {}", settings.controls.name()),
+            SettingsEntry::AsteroidsBounce => {
+                format!("Asteroids bounce off each other: {}", on_off(settings.asteroids_bounce))
+            }
+            SettingsEntry::DamageNumbers => {
+                format!("Damage numbers: {}", on_off(settings.show_damage_numbers))
+            }
+            SettingsEntry::EventFeed => format!("Event feed: {}", on_off(settings.show_event_feed)),
+            SettingsEntry::DangerVignette => {
+                format!("Danger vignette: {}", on_off(settings.show_danger_vignette))
+            }
+            SettingsEntry::Trajectories => {
+                format!("Practice trajectories: {}", on_off(settings.show_trajectories))
+            }
+            SettingsEntry::FlightPath => format!("Flight path assist: {}", on_off(settings.show_flight_path)),
+            SettingsEntry::Back => "Back".to_string(),
+        }
+    }
+
+    /// steps the setting on or back, or flips it if it's on or off
+    fn change(&self, settings: &mut Settings, choice: Choice) {
+        let step = if choice == Choice::Next { VOLUME_STEP } else { -VOLUME_STEP };
+        match self {
+            SettingsEntry::MusicVolume => {
+                settings.music_volume = (settings.music_volume + step).clamp(0., 1.)
+            }
+            SettingsEntry::EffectsVolume => {
+                settings.effects_volume = (settings.effects_volume + step).clamp(0., 1.)
+            }
+            SettingsEntry::Difficulty => {
+                settings.difficulty = match choice {
+                    Choice::Next => settings.difficulty.next(),
+                    Choice::Previous => settings.difficulty.previous(),
+                }
+            }
+            SettingsEntry::Density => {
+                settings.density = match choice {
+                    Choice::Next => settings.density.next(),
+                    Choice::Previous => settings.density.previous(),
+                }
+            }
+            SettingsEntry::Controls => settings.controls = settings.controls.next(),
+            SettingsEntry::AsteroidsBounce => settings.asteroids_bounce = !settings.asteroids_bounce,
+            SettingsEntry::DamageNumbers => settings.show_damage_numbers = !settings.show_damage_numbers,
+            SettingsEntry::EventFeed => settings.show_event_feed = !settings.show_event_feed,
+            SettingsEntry::DangerVignette => settings.show_danger_vignette = !settings.show_danger_vignette,
+            SettingsEntry::Trajectories => settings.show_trajectories = !settings.show_trajectories,
+            SettingsEntry::FlightPath => settings.show_flight_path = !settings.show_flight_path,
+            SettingsEntry::Back => {}
+        }
+    }
+}
+
+/// what the menus remember between frames
+#[derive(Default)]
+pub struct Menus {
+    export_message: Option<String>,
+    /// the what's new screen, while it's open
+    changelog: Option<ChangelogScreen>,
+    showing_high_scores: bool,
+    title_list: OptionList,
+    high_scores: HighScores,
+    /// what has been typed so far on the name entry screen
+    initials: String,
+    /// what has been typed so far on the share code screen, while it's up
+    share_code_entry: Option<String>,
+    /// whether the last share code entered couldn't be read
+    share_code_invalid: bool,
+    /// things the player should know about, shown on the title screen
+    /// until the first game starts
+    notices: Vec<String>,
+    settings_list: OptionList,
+}
+impl Menus {
+    pub fn new(storage: &dyn Storage) -> Result<Menus, StartupError> {
+        let (high_scores, notice) = HighScores::load(storage)?;
+        Ok(Menus {
+            high_scores,
+            notices: notice.into_iter().collect(),
+            ..Menus::default()
+        })
+    }
+
+    /// shows a notice on the title screen until the first game starts
+    pub fn notify(&mut self, notice: String) {
+        self.notices.push(notice);
+    }
+
+    /// shows a notice for a setting that couldn't be saved
+    fn notify_on_error(&mut self, result: Result<(), String>) {
+        if let Err(err) = result {
+            self.notify(err);
+        }
+    }
+
+    /// whether the run that just ended earns a place on the high score
+    /// table. If it does, the name entry screen starts out blank.
+    pub fn made_high_score(&mut self, game: &Game) -> bool {
+        if game.rules.practice || game.world.cheated || !self.high_scores.qualifies(game.report.score) {
+            return false;
+        }
+        self.initials.clear();
+        // letters typed during the run shouldn't end up in the initials
+        while get_char_pressed().is_some() {}
+        true
+    }
+
+    /// takes the player's initials for the high score table. Returns whether
+    /// they're done and the run has been added.
+    pub fn enter_initials(&mut self, game: &Game) -> bool {
+        while let Some(letter) = get_char_pressed() {
+            if letter.is_ascii_alphanumeric() && self.initials.len() < MAX_INITIALS {
+                self.initials.push(letter.to_ascii_uppercase());
+            }
+        }
+        if is_key_pressed(KeyCode::Backspace) {
+            self.initials.pop();
+        }
+        if !is_key_pressed(KeyCode::Enter) {
+            return false;
+        }
+
+        self.high_scores.insert(HighScore {
+            score: game.report.score,
+            initials: std::mem::take(&mut self.initials),
+            wave: game.world.wave,
+        });
+        if let Err(err) = self.high_scores.save(game.storage.as_ref()) {
+            self.export_message = Some(err);
+        }
+        true
+    }
+
+    /// takes a share code typed or pasted on the title screen. Once a valid
+    /// one is entered, the options it was played with are set and its run
+    /// starts.
+    fn enter_share_code(&mut self, game: &mut Game) -> MenuChoice {
+        let Some(entry) = self.share_code_entry.as_mut() else {
+            return MenuChoice::Stay;
+        };
+        while let Some(letter) = get_char_pressed() {
+            if (letter.is_ascii_alphanumeric() || letter == '-') && entry.len() < MAX_SHARE_CODE_LEN {
+                entry.push(letter.to_ascii_uppercase());
+            }
+        }
+        let modifier = [KeyCode::LeftControl, KeyCode::RightControl, KeyCode::LeftSuper, KeyCode::RightSuper];
+        if is_key_pressed(KeyCode::V) && modifier.iter().any(|key| is_key_down(*key)) {
+            if let Some(text) = paste_from_clipboard() {
+                *entry = text.trim().to_ascii_uppercase();
+                entry.truncate(MAX_SHARE_CODE_LEN);
+            }
+        }
+        if is_key_pressed(KeyCode::Backspace) {
+            entry.pop();
+        }
+        if is_key_pressed(KeyCode::Escape) {
+            self.share_code_entry = None;
+            return MenuChoice::Stay;
+        }
+        if !is_key_pressed(KeyCode::Enter) {
+            return MenuChoice::Stay;
+        }
+
+        let Some(code) = ShareCode::parse(entry) else {
+            self.share_code_invalid = true;
+            return MenuChoice::Stay;
+        };
+        game.settings.density = code.density;
+        game.settings.difficulty = code.difficulty;
+        self.notify_on_error(game.save_settings());
+        game.rules.edges = code.edges;
+        game.rules.practice = code.practice;
+        self.share_code_entry = None;
+        self.notices.clear();
+        MenuChoice::PlaySeeded(code.seed)
+    }
+
+    /// handles the keys on the title and game over screens. `after_run` is
+    /// whether there is a finished run to export.
+    pub fn update(&mut self, game: &mut Game, after_run: bool) -> MenuChoice {
+        if let Some(changelog) = self.changelog.as_mut() {
+            changelog.update();
+            if is_key_pressed(KeyCode::Escape) || is_key_pressed(KeyCode::W) {
+                self.changelog = None;
+            }
+            return MenuChoice::Stay;
+        }
+        if self.showing_high_scores {
+            if is_key_pressed(KeyCode::Escape) || is_key_pressed(KeyCode::Enter) {
+                self.showing_high_scores = false;
+            }
+            return MenuChoice::Stay;
+        }
+        if self.share_code_entry.is_some() {
+            return self.enter_share_code(game);
+        }
+
+        let choice = if after_run {
+            // Reset the Game on Enter
+            if is_key_pressed(KeyCode::Enter) {
+                MenuChoice::Play
+            } else {
+                MenuChoice::Stay
+            }
+        } else if self.title_list.update(TITLE_ENTRIES.len()) == Some(Choice::Next) {
+            match self.title_list.selected {
+                0 => MenuChoice::Play,
+                1 => {
+                    // letters typed before shouldn't end up in the code
+                    while get_char_pressed().is_some() {}
+                    self.share_code_entry = Some(String::new());
+                    self.share_code_invalid = false;
+                    MenuChoice::Stay
+                }
+                2 => {
+                    self.showing_high_scores = true;
+                    MenuChoice::Stay
+                }
+                3 => MenuChoice::Settings,
+                _ => MenuChoice::Quit,
+            }
+        } else if is_key_pressed(KeyCode::S) {
+            MenuChoice::Settings
+        } else {
+            MenuChoice::Stay
+        };
+        if choice == MenuChoice::Play {
+            self.export_message = None;
+            self.notices.clear();
+        }
+        if choice != MenuChoice::Stay {
+            return choice;
+        }
+
+        if is_key_pressed(KeyCode::W) {
+            self.changelog = Some(ChangelogScreen::open());
+        }
+        if is_key_pressed(KeyCode::D) {
+            game.settings.density = game.settings.density.next();
+            self.notify_on_error(game.save_settings());
+        }
+        if is_key_pressed(KeyCode::A) {
+            game.rules.edges = game.rules.edges.next();
+        }
+        if is_key_pressed(KeyCode::P) {
+            game.rules.practice = !game.rules.practice;
+        }
+        if after_run {
+            if is_key_pressed(KeyCode::J) {
+                self.export_message = Some(export_report(&game.report, true, game.storage.as_ref()));
+            } else if is_key_pressed(KeyCode::C) {
+                self.export_message = Some(export_report(&game.report, false, game.storage.as_ref()));
+            } else if is_key_pressed(KeyCode::X) {
+                copy_to_clipboard(&game.report.share_code);
+                self.export_message = Some(format!("Copied share code {}", game.report.share_code));
+            }
+        }
+        MenuChoice::Stay
+    }
+
+    /// handles the keys on the settings screen, saving any change straight
+    /// away. Returns whether the player is done with it.
+    pub fn update_settings(&mut self, game: &mut Game) -> bool {
+        if is_key_pressed(KeyCode::Escape) {
+            self.settings_list.selected = 0;
+            return true;
+        }
+        let Some(choice) = self.settings_list.update(SETTINGS_ENTRIES.len()) else {
+            return false;
+        };
+        let entry = SETTINGS_ENTRIES[self.settings_list.selected];
+        if entry == SettingsEntry::Back {
+            let done = choice == Choice::Next;
+            if done {
+                self.settings_list.selected = 0;
+            }
+            return done;
+        }
+        entry.change(&mut game.settings, choice);
+        game.audio.apply_volumes(&game.settings);
+        self.notify_on_error(game.save_settings());
+        false
+    }
+
+    pub fn draw_title(&self, game: &Game) {
+        if let Some(changelog) = &self.changelog {
+            changelog.draw();
+            return;
+        }
+
+        clear_background(LIGHTGRAY);
+        game.draw_background();
+        if self.showing_high_scores {
+            self.draw_high_score_screen();
+            return;
+        }
+        if let Some(entry) = self.share_code_entry.as_deref() {
+            self.draw_share_code_entry(entry);
+            return;
+        }
+
+        draw_centered_text("ASTEROIDS", screen_height() / 3., 60.);
+        let entries = TITLE_ENTRIES.map(str::to_string);
+        self.title_list.draw(&entries, screen_height() / 2. - 20.);
+        let below_entries = screen_height() / 2. - 20. + TITLE_ENTRIES.len() as f32 * 30.;
+        self.draw_options(game, below_entries + 10., None);
+
+        for (line, notice) in self.notices.iter().rev().enumerate() {
+            draw_centered_text(notice, screen_height() - 30. - line as f32 * 20., 16.);
+        }
+    }
+
+    /// draws the result of the last run, what stood out about it, and the
+    /// options that can be changed before the next one
+    pub fn draw_game_over(&self, game: &Game) {
+        if let Some(changelog) = &self.changelog {
+            changelog.draw();
+            return;
+        }
+
+        let text = &format!("Game over on wave {}. Press enter to play again.", game.world.wave);
+        let font_size = 23.;
+
+        let text_size = measure_text(text, None, font_size as _, 1.0);
+        draw_text(
+            text,
+            screen_width() / 2. - text_size.width / 2.,
+            screen_height() / 2. - text_size.height / 2.,
+            font_size,
+            DARKGRAY,
+        );
+
+        let insights = analyze_run(&game.report.log, game.report.duration_secs);
+        for (line, insight) in insights.iter().enumerate() {
+            draw_centered_text(
+                insight,
+                screen_height() / 2. - text_size.height * 2. - (insights.len() - line) as f32 * 22.,
+                18.,
+            );
+        }
+
+        self.draw_high_scores();
+
+        let export_text = self
+            .export_message
+            .as_deref()
+            .unwrap_or("Export this run: J for JSON, C for CSV");
+        let below_title = screen_height() / 2. + text_size.height * 2.;
+        let share_text = format!("Share code: {} (X to copy)", game.report.share_code);
+        draw_centered_text(&share_text, below_title, 18.);
+        self.draw_options(game, below_title + 24., Some(export_text));
+    }
+
+    /// the high score table, down the left side of the screen
+    fn draw_high_scores(&self) {
+        if self.high_scores.entries.is_empty() {
+            return;
+        }
+        draw_text("High scores", 30., 50., 24., DARKGRAY);
+        for (place, line) in self.high_score_lines().iter().enumerate() {
+            draw_text(line, 30., 80. + place as f32 * 22., 20., DARKGRAY);
+        }
+    }
+
+    /// the high score table on a screen of its own, from the title screen
+    fn draw_high_score_screen(&self) {
+        draw_centered_text("High Scores", screen_height() / 4., 40.);
+        let lines = self.high_score_lines();
+        if lines.is_empty() {
+            draw_centered_text("No high scores yet", screen_height() / 4. + 50., 20.);
+        }
+        for (place, line) in lines.iter().enumerate() {
+            draw_centered_text(line, screen_height() / 4. + 50. + place as f32 * 24., 20.);
+        }
+        draw_centered_text("Press enter to go back", screen_height() - 40., 16.);
+    }
+
+    /// one line per entry in the high score table
+    fn high_score_lines(&self) -> Vec<String> {
+        self.high_scores
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(place, entry)| {
+                let initials = if entry.initials.is_empty() { "---" } else { &entry.initials };
+                format!("{:>2}. {:<3} {:>7}  wave {}", place + 1, initials, entry.score, entry.wave)
+            })
+            .collect()
+    }
+
+    /// asks for a share code to play
+    fn draw_share_code_entry(&self, entry: &str) {
+        draw_centered_text("Play a Share Code", screen_height() / 3., 40.);
+        let shown = if entry.is_empty() { "_" } else { entry };
+        draw_centered_text(shown, screen_height() / 2., 32.);
+        if self.share_code_invalid {
+            let y = screen_height() / 2. + 30.;
+            draw_centered_text("That isn't a share code. They look like CW-1A2B3C4D.", y, 18.);
+        }
+        draw_centered_text(
+            "Type or paste (Ctrl+V) a code and press enter, or Escape to go back",
+            screen_height() / 2. + 60.,
+            18.,
+        );
+    }
+
+    /// asks for initials over the frozen end of the run
+    pub fn draw_name_entry(&self, game: &Game) {
+        draw_rectangle(0., 0., screen_width(), screen_height(), Color::new(0.8, 0.8, 0.8, 0.6));
+        draw_centered_text(
+            &format!("New high score: {}", game.report.score),
+            screen_height() / 2. - 30.,
+            40.,
+        );
+        let blanks = "_".repeat(MAX_INITIALS - self.initials.len());
+        draw_centered_text(&format!("{}{}", self.initials, blanks), screen_height() / 2. + 10., 32.);
+        draw_centered_text(
+            "Type your initials and press enter",
+            screen_height() / 2. + 40.,
+            18.,
+        );
+    }
+
+    /// the settings, one per line, with the highlighted one marked
+    pub fn draw_settings(&self, game: &Game) {
+        let entries: Vec<String> = SETTINGS_ENTRIES.iter().map(|entry| entry.label(&game.settings)).collect();
+
+        draw_rectangle(0., 0., screen_width(), screen_height(), Color::new(0.8, 0.8, 0.8, 0.9));
+        draw_centered_text("Settings", screen_height() / 4., 40.);
+        self.settings_list.draw(&entries, screen_height() / 4. + 50.);
+        draw_centered_text(
+            "Up and down to choose, left and right to change, Escape to go back",
+            screen_height() - 40.,
+            16.,
+        );
+        for (line, notice) in self.notices.iter().rev().enumerate() {
+            draw_centered_text(notice, screen_height() - 70. - line as f32 * 20., 16.);
+        }
+    }
+
+    /// lists the options for the next game, starting at `y`
+    fn draw_options(&self, game: &Game, y: f32, export_text: Option<&str>) {
+        let options = [
+            export_text.map(str::to_string),
+            Some(format!("Asteroid density (D): {}", game.settings.density.name())),
+            Some(format!("Screen edges (A): {}", game.rules.edges.name())),
+            Some(format!("Practice mode (P): {}", if game.rules.practice { "on" } else { "off" })),
+            Some("What's new: W".to_string()),
+        ];
+        for (line, option) in options.iter().flatten().enumerate() {
+            draw_centered_text(option, y + line as f32 * 24., 18.);
+        }
+    }
+}
+
+/// dims the frozen game and says how to carry on
+pub fn draw_paused() {
+    draw_rectangle(0., 0., screen_width(), screen_height(), Color::new(0.8, 0.8, 0.8, 0.6));
+    draw_centered_text("Paused", screen_height() / 2., 40.);
+    draw_centered_text("Press P or Escape to resume", screen_height() / 2. + 30., 18.);
+    draw_centered_text("Settings: S", screen_height() / 2. + 54., 18.);
+}
@@ -0,0 +1,118 @@
+//! upgrading files written by older versions of the game. Each saved format
+//! has a list of migrations, one per version, that each bring the file's
+//! JSON up from the version before.
+
+use serde_json::Value;
+
+/// brings a file's JSON up one version
+pub type Migration = fn(&mut Value);
+
+/// quicksaves and the snapshots in bug reports
+pub const SAVE_MIGRATIONS: &[Migration] = &[save_v1_world_clock];
+/// the high score table. Nothing has changed since it was first written.
+pub const HIGH_SCORE_MIGRATIONS: &[Migration] = &[];
+/// the player's settings. Nothing has changed since they were first written.
+pub const SETTINGS_MIGRATIONS: &[Migration] = &[];
+
+/// brings `file` up to the latest version by running every migration it
+/// hasn't had yet. Files from before versions were recorded count as version
+/// 0. Returns whether anything needed upgrading, or an error if the file is
+/// from a newer version of the game than this one.
+pub fn upgrade(file: &mut Value, migrations: &[Migration]) -> Result<bool, String> {
+    let version = file.get("version").and_then(Value::as_u64).unwrap_or(0) as usize;
+    if version > migrations.len() {
+        return Err(format!(
+            "it was written by a newer version of the game (format {}, this one reads up to {})",
+            version,
+            migrations.len()
+        ));
+    }
+
+    for migration in &migrations[version..] {
+        migration(file);
+    }
+    stamp(file, migrations);
+    Ok(version < migrations.len())
+}
+
+/// marks `file` as being at the latest version, for writing it out
+pub fn stamp(file: &mut Value, migrations: &[Migration]) {
+    if let Some(fields) = file.as_object_mut() {
+        fields.insert("version".to_string(), migrations.len().into());
+    }
+}
+
+/// the world used to take its clock from the snapshot around it when
+/// restored. Now it keeps its own, so older saves get the snapshot's.
+fn save_v1_world_clock(save: &mut Value) {
+    let time = save.get("time").cloned().unwrap_or_else(|| 0.0.into());
+    if let Some(world) = save.get_mut("world").and_then(Value::as_object_mut) {
+        world.entry("time").or_insert(time);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn unversioned_files_count_as_version_zero() {
+        let mut file = json!({ "entries": [] });
+        let upgraded = upgrade(&mut file, &[|file| file["touched"] = true.into()]).unwrap();
+        assert!(upgraded);
+        assert_eq!(file["touched"], true);
+        assert_eq!(file["version"], 1);
+    }
+
+    #[test]
+    fn only_missing_migrations_run() {
+        let migrations: &[Migration] = &[
+            |file| file["first"] = true.into(),
+            |file| file["second"] = true.into(),
+        ];
+        let mut file = json!({ "version": 1 });
+        assert!(upgrade(&mut file, migrations).unwrap());
+        assert!(file.get("first").is_none());
+        assert_eq!(file["second"], true);
+        assert_eq!(file["version"], 2);
+    }
+
+    #[test]
+    fn current_files_are_left_alone() {
+        let mut file = json!({ "version": 1, "time": 3.0, "world": { "time": 5.0 } });
+        let before = file.clone();
+        assert!(!upgrade(&mut file, SAVE_MIGRATIONS).unwrap());
+        assert_eq!(file, before);
+    }
+
+    #[test]
+    fn files_from_newer_versions_are_refused() {
+        let mut file = json!({ "version": 99 });
+        assert!(upgrade(&mut file, SAVE_MIGRATIONS).is_err());
+        assert_eq!(file["version"], 99);
+    }
+
+    #[test]
+    fn save_v1_copies_the_snapshot_clock_into_the_world() {
+        let mut save = json!({ "time": 12.5, "rng_seed": 7, "world": { "lives": 3 } });
+        assert!(upgrade(&mut save, SAVE_MIGRATIONS).unwrap());
+        assert_eq!(save["world"]["time"], 12.5);
+        assert_eq!(save["version"], 1);
+    }
+
+    #[test]
+    fn save_v1_keeps_a_clock_the_world_already_has() {
+        let mut save = json!({ "time": 12.5, "world": { "time": 4.0 } });
+        upgrade(&mut save, SAVE_MIGRATIONS).unwrap();
+        assert_eq!(save["world"]["time"], 4.0);
+    }
+
+    #[test]
+    fn high_score_tables_only_gain_a_version() {
+        let mut table = json!({ "entries": [{ "score": 100, "initials": "AB", "wave": 2 }] });
+        assert!(!upgrade(&mut table, HIGH_SCORE_MIGRATIONS).unwrap());
+        assert_eq!(table["version"], 0);
+        assert_eq!(table["entries"][0]["score"], 100);
+    }
+}
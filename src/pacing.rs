@@ -0,0 +1,33 @@
+//! slowing the frame rate down when nothing on screen is moving
+
+use macroquad::prelude::*;
+
+/// frames per second while a menu or the pause screen is up
+const IDLE_FPS: f64 = 10.;
+
+/// holds frames back while the game is idle. In play frames come as fast as
+/// the display takes them; on a still screen there's nothing to redraw, so
+/// it sleeps most of each frame away to save battery.
+pub struct FramePacer {
+    last_frame: f64,
+}
+impl FramePacer {
+    pub fn new() -> FramePacer {
+        FramePacer {
+            last_frame: get_time(),
+        }
+    }
+
+    /// waits out the rest of an idle frame, if `idle`, before the next one
+    pub fn wait(&mut self, idle: bool) {
+        // a browser paces frames itself and can't be put to sleep
+        #[cfg(not(target_arch = "wasm32"))]
+        if idle {
+            let remaining = 1. / IDLE_FPS - (get_time() - self.last_frame);
+            if remaining > 0. {
+                std::thread::sleep(std::time::Duration::from_secs_f64(remaining));
+            }
+        }
+        self.last_frame = get_time();
+    }
+}
@@ -0,0 +1,178 @@
+//! debris flung out when things are destroyed. How many particles there
+//! are is kept to a budget that shrinks when frames start taking too long,
+//! so a big chain of explosions doesn't slow the game down on weak
+//! hardware.
+
+use crate::physics::{Point, Velocity};
+use crate::random;
+use macroquad::prelude::*;
+
+/// the most particles in flight at once, when frames are quick
+const MAX_PARTICLES: usize = 2000;
+/// seconds a frame takes when all is well, and when it has got so slow that
+/// the budget is cut as far as it goes
+const SMOOTH_FRAME_TIME: f32 = 1. / 55.;
+const SLOW_FRAME_TIME: f32 = 1. / 30.;
+/// how quickly the measured load follows the frame time. Lower is steadier.
+const LOAD_EASING: f32 = 0.1;
+/// how far each measure is cut at full load: the particle cap, the
+/// particles in each burst and how long each one lasts
+const CAP_CUT: f32 = 0.75;
+const BURST_CUT: f32 = 0.7;
+const LIFETIME_CUT: f32 = 0.5;
+/// the load past which bursts this close to one already thrown this step
+/// are merged into it
+const MERGE_LOAD: f32 = 0.5;
+const MERGE_DISTANCE: f32 = 30.;
+
+/// a speck of debris that drifts and fades out over its lifetime
+pub struct Particle {
+    pub pos: Point,
+    pub vel: Velocity,
+    pub start_time: f64,
+    /// seconds from appearing to fading out completely
+    pub lifetime: f64,
+    pub color: Color,
+}
+
+/// every particle currently in flight
+#[derive(Default)]
+pub struct Particles {
+    pub particles: Vec<Particle>,
+    /// how hard pressed the game is for time, from 0. (frames are quick) to
+    /// 1. (frames are slow), eased so one slow frame doesn't count for much
+    load: f32,
+    /// where bursts were thrown this step, for merging ones on top of each
+    /// other, and when that step was
+    bursts: Vec<Point>,
+    bursts_time: f64,
+}
+impl Particles {
+    /// takes in how long the last frame took, for sizing the budget
+    pub fn measure(&mut self, frame_time: f32) {
+        let load = (frame_time - SMOOTH_FRAME_TIME) / (SLOW_FRAME_TIME - SMOOTH_FRAME_TIME);
+        self.load += (load.clamp(0., 1.) - self.load) * LOAD_EASING;
+    }
+
+    /// the most particles there can be in flight right now
+    fn cap(&self) -> usize {
+        (MAX_PARTICLES as f32 * (1. - self.load * CAP_CUT)) as usize
+    }
+
+    /// throws `count` particles out from `pos` in every direction. They
+    /// carry on with `vel`, the velocity of whatever broke apart. Under load
+    /// there are fewer of them, they fade sooner, and a burst on top of
+    /// another one this step adds nothing.
+    pub fn burst(&mut self, pos: Point, vel: Velocity, count: usize, color: Color, now: f64) {
+        if now != self.bursts_time {
+            self.bursts.clear();
+            self.bursts_time = now;
+        }
+        if self.load > MERGE_LOAD && self.bursts.iter().any(|burst| burst.distance(&pos) < MERGE_DISTANCE) {
+            return;
+        }
+        self.bursts.push(pos);
+
+        let count = ((count as f32 * (1. - self.load * BURST_CUT)).ceil() as usize)
+            .min(self.cap().saturating_sub(self.particles.len()));
+        let lifetime_scale = 1. - self.load * LIFETIME_CUT;
+        for _ in 0..count {
+            let (mut particle_vel, lifetime) =
+                random::effects(|rng| (rng.velocity(20., 120.), rng.range(0.4, 1.)));
+            particle_vel.add_velocity(vel);
+            self.particles.push(Particle {
+                pos,
+                vel: particle_vel,
+                start_time: now,
+                lifetime: (lifetime * lifetime_scale) as f64,
+                color,
+            });
+        }
+    }
+
+    /// leaves a slow, short-lived puff of smoke at `pos`, for a trail
+    /// behind something. None is left once the budget is spent.
+    pub fn smoke(&mut self, pos: Point, now: f64) {
+        if self.particles.len() >= self.cap() {
+            return;
+        }
+        let lifetime_scale = 1. - self.load * LIFETIME_CUT;
+        let (vel, lifetime) = random::effects(|rng| (rng.velocity(0., 10.), rng.range(0.3, 0.6)));
+        self.particles.push(Particle {
+            pos,
+            vel,
+            start_time: now,
+            lifetime: (lifetime * lifetime_scale) as f64,
+            color: GRAY,
+        });
+    }
+
+    pub fn advance(&mut self, delta: f32) {
+        for particle in self.particles.iter_mut() {
+            particle.pos.x += particle.vel.x * delta;
+            particle.pos.y += particle.vel.y * delta;
+        }
+    }
+
+    /// drops particles that have faded out
+    pub fn expire(&mut self, now: f64) {
+        self.particles.retain(|particle| particle.start_time + particle.lifetime > now);
+    }
+
+    pub fn draw(&self, now: f64) {
+        for particle in self.particles.iter() {
+            let age = ((now - particle.start_time) / particle.lifetime) as f32;
+            let mut color = particle.color;
+            color.a = 1. - age;
+            draw_circle(particle.pos.x, particle.pos.y, 1.5, color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slowed_down() -> Particles {
+        let mut particles = Particles::default();
+        for _ in 0..200 {
+            particles.measure(SLOW_FRAME_TIME * 2.);
+        }
+        particles
+    }
+
+    #[test]
+    fn slow_frames_thin_out_bursts() {
+        let mut quick = Particles::default();
+        quick.burst(Point::default(), Velocity::default(), 30, GRAY, 0.);
+        let mut slow = slowed_down();
+        slow.burst(Point::default(), Velocity::default(), 30, GRAY, 0.);
+
+        assert_eq!(quick.particles.len(), 30);
+        assert!(slow.particles.len() < 15, "{}", slow.particles.len());
+        assert!(slow.particles.iter().all(|particle| particle.lifetime <= 0.5));
+    }
+
+    #[test]
+    fn bursts_on_top_of_each_other_merge_under_load() {
+        let mut particles = slowed_down();
+        particles.burst(Point::default(), Velocity::default(), 30, GRAY, 0.);
+        let after_first = particles.particles.len();
+        particles.burst(Point { x: 5., y: 5. }, Velocity::default(), 30, GRAY, 0.);
+        assert_eq!(particles.particles.len(), after_first);
+
+        // the next step starts afresh
+        particles.burst(Point { x: 5., y: 5. }, Velocity::default(), 30, GRAY, 0.1);
+        assert!(particles.particles.len() > after_first);
+    }
+
+    #[test]
+    fn the_cap_holds_however_many_bursts_there_are() {
+        let mut particles = slowed_down();
+        for burst in 0..500 {
+            let pos = Point { x: burst as f32 * 100., y: 0. };
+            particles.burst(pos, Velocity::default(), 30, GRAY, 0.);
+        }
+        assert!(particles.particles.len() <= particles.cap());
+    }
+}
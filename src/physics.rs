@@ -0,0 +1,177 @@
+//! positions, velocities and what happens at the edges of the screen
+
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, Copy, Clone, Serialize, Deserialize)]
+pub struct Point {
+    pub x: f32,
+    pub y: f32,
+}
+impl Point {
+    /// finds the distance between this point and another point
+    pub fn distance(&self, point: &Point) -> f32 {
+        // self :x2/y2
+        // point: x1/y1
+        ((self.x - point.x).powi(2) + (self.y - point.y).powi(2)).sqrt()
+    }
+}
+
+#[derive(Debug, Default, Copy, Clone, Serialize, Deserialize)]
+pub struct Velocity {
+    pub x: f32,
+    pub y: f32,
+}
+impl Velocity {
+    pub fn add_at_angle(&mut self, velocity: f32, angle: f32) {
+        let radians = angle.to_radians();
+
+        self.x += radians.sin() * velocity;
+        self.y += -radians.cos() * velocity;
+    }
+
+    pub fn add_velocity(&mut self, velocity: Velocity) {
+        self.x += velocity.x;
+        self.y += velocity.y;
+    }
+}
+
+/// how fast time passes for one entity compared to the rest of the game.
+/// Movement, weapons and animations all go through it, so an effect only has
+/// to change an entity's time scale to slow it down or speed it up.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimeScale(pub f32);
+impl Default for TimeScale {
+    fn default() -> TimeScale {
+        TimeScale(1.)
+    }
+}
+impl TimeScale {
+    /// how much of `amount` happens for this entity
+    pub fn apply(&self, amount: f32) -> f32 {
+        amount * self.0
+    }
+}
+
+/// simulation steps per second
+pub const TICK_RATE: f32 = 120.;
+/// seconds per simulation step
+pub const TICK: f32 = 1. / TICK_RATE;
+
+/// the longest a single frame is allowed to step the game, in seconds. A
+/// hitch, like dragging the window, would otherwise fling everything across
+/// the screen in one step.
+pub const MAX_FRAME_TIME: f32 = 0.1;
+
+/// seconds since the last frame, for scaling movement so the game runs at
+/// the same speed whatever the monitor's refresh rate
+pub fn frame_delta() -> f32 {
+    get_frame_time().min(MAX_FRAME_TIME)
+}
+
+/// the width and height of the playfield, which fills the window. Tests
+/// have no window, so they play on one the game's default size.
+pub fn field_size() -> (f32, f32) {
+    if cfg!(test) {
+        (800., 600.)
+    } else {
+        (screen_width(), screen_height())
+    }
+}
+
+/// a point `amount` of the way from `from` to `to`, for drawing between two
+/// simulation steps. Something that wrapped around the screen in between
+/// jumps straight to `to` rather than streaking across.
+pub fn interpolate(from: Point, to: Point, amount: f32) -> Point {
+    let (width, height) = field_size();
+    if from.distance(&to) > width.min(height) / 2. {
+        return to;
+    }
+    Point {
+        x: from.x + (to.x - from.x) * amount,
+        y: from.y + (to.y - from.y) * amount,
+    }
+}
+
+/// Wraps objects when they hit the edge of the screen
+pub fn wrap_around(point: &mut Point) {
+    let (width, height) = field_size();
+    if point.x > width {
+        point.x = 0.;
+    }
+    if point.x < 0. {
+        point.x = width
+    }
+
+    if point.y > height {
+        point.y = 0.;
+    }
+    if point.y < 0. {
+        point.y = height
+    }
+}
+
+/// keeps a round object inside the arena, reflecting its velocity off any
+/// wall it has crossed
+pub fn bounce_off_walls(pos: &mut Point, vel: &mut Velocity, radius: f32) {
+    let (width, height) = field_size();
+    if pos.x - radius < 0. {
+        pos.x = radius;
+        vel.x = vel.x.abs();
+    } else if pos.x + radius > width {
+        pos.x = width - radius;
+        vel.x = -vel.x.abs();
+    }
+
+    if pos.y - radius < 0. {
+        pos.y = radius;
+        vel.y = vel.y.abs();
+    } else if pos.y + radius > height {
+        pos.y = height - radius;
+        vel.y = -vel.y.abs();
+    }
+}
+
+/// bounces two round objects off each other if they overlap, as an elastic
+/// collision with each one's mass in proportion to its radius, and pushes
+/// them apart so they stop overlapping
+pub fn bounce_apart(
+    a_pos: &mut Point,
+    a_vel: &mut Velocity,
+    a_radius: f32,
+    b_pos: &mut Point,
+    b_vel: &mut Velocity,
+    b_radius: f32,
+) {
+    let distance = a_pos.distance(b_pos);
+    let overlap = a_radius + b_radius - distance;
+    if overlap <= 0. || distance == 0. {
+        return;
+    }
+    // the direction from a to b
+    let normal_x = (b_pos.x - a_pos.x) / distance;
+    let normal_y = (b_pos.y - a_pos.y) / distance;
+    let total_mass = a_radius + b_radius;
+
+    // heavier objects get pushed less
+    a_pos.x -= normal_x * overlap * b_radius / total_mass;
+    a_pos.y -= normal_y * overlap * b_radius / total_mass;
+    b_pos.x += normal_x * overlap * a_radius / total_mass;
+    b_pos.y += normal_y * overlap * a_radius / total_mass;
+
+    // only trade speed if they're still closing in on each other
+    let closing = (a_vel.x - b_vel.x) * normal_x + (a_vel.y - b_vel.y) * normal_y;
+    if closing <= 0. {
+        return;
+    }
+    let impulse = 2. * closing / total_mass;
+    a_vel.x -= impulse * b_radius * normal_x;
+    a_vel.y -= impulse * b_radius * normal_y;
+    b_vel.x += impulse * a_radius * normal_x;
+    b_vel.y += impulse * a_radius * normal_y;
+}
+
+pub fn in_arena(point: &Point) -> bool {
+    let (width, height) = field_size();
+    point.x >= 0. && point.x <= width && point.y >= 0. && point.y <= height
+}
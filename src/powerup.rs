@@ -0,0 +1,106 @@
+//! power-ups dropped by destroyed asteroids for the ship to collect
+
+use crate::physics::{Point, TimeScale, Velocity};
+use crate::random::{Rng, WeightedTable};
+use serde::{Deserialize, Serialize};
+
+pub const POWERUP_SIZE: f32 = 8.;
+/// seconds a power-up drifts before it's gone
+pub const POWERUP_LIFETIME: f64 = 10.;
+/// chance for a destroyed asteroid to drop a power-up
+pub const POWERUP_DROP_CHANCE: f32 = 0.08;
+/// seconds rapid fire lasts once collected
+pub const RAPID_FIRE_TIME: f64 = 10.;
+/// seconds the spread gun lasts once collected
+pub const SPREAD_SHOT_TIME: f64 = 10.;
+/// missiles a missiles power-up gives
+pub const MISSILE_AMMO: u32 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum PowerUpKind {
+    /// takes one hit that would have destroyed the ship
+    Shield,
+    /// halves the time between shots for a while
+    RapidFire,
+    /// a while with the spread gun, which fires three bullets at once
+    SpreadShot,
+    /// ammo for the missile launcher
+    Missiles,
+    /// one more smart bomb
+    Bomb,
+}
+impl PowerUpKind {
+    /// which power-up a destroyed asteroid drops
+    pub fn random(rng: &mut Rng) -> PowerUpKind {
+        let table = WeightedTable::new(vec![
+            (1., PowerUpKind::Shield),
+            (1., PowerUpKind::RapidFire),
+            (1., PowerUpKind::SpreadShot),
+            (1., PowerUpKind::Missiles),
+            (1., PowerUpKind::Bomb),
+        ]);
+        *table.pick(rng)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PowerUpKind::Shield => "Shield",
+            PowerUpKind::RapidFire => "Rapid fire",
+            PowerUpKind::SpreadShot => "Spread shot",
+            PowerUpKind::Missiles => "Missiles",
+            PowerUpKind::Bomb => "Smart bomb",
+        }
+    }
+
+    /// the letter drawn on the pickup
+    pub fn letter(&self) -> &'static str {
+        match self {
+            PowerUpKind::Shield => "S",
+            PowerUpKind::RapidFire => "R",
+            PowerUpKind::SpreadShot => "3",
+            PowerUpKind::Missiles => "M",
+            PowerUpKind::Bomb => "B",
+        }
+    }
+}
+
+/// a power-up drifting where an asteroid broke up, until the ship flies
+/// through it or it runs out of time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PowerUp {
+    pub kind: PowerUpKind,
+    pub pos: Point,
+    pub vel: Velocity,
+    pub spawn_time: f64,
+    pub collected: bool,
+    #[serde(default)]
+    pub time_scale: TimeScale,
+    /// where the power-up was at the start of the last simulation step
+    #[serde(skip)]
+    pub last_pos: Point,
+}
+impl PowerUp {
+    /// a power-up left behind by something at `pos` moving at `parent_vel`.
+    /// It keeps some of that speed, so it drifts on the same way.
+    pub fn drop(kind: PowerUpKind, pos: Point, parent_vel: Velocity, now: f64, rng: &mut Rng) -> PowerUp {
+        let wobble = rng.velocity(5., 15.);
+        PowerUp {
+            kind,
+            pos,
+            vel: Velocity {
+                x: parent_vel.x * 0.5 + wobble.x,
+                y: parent_vel.y * 0.5 + wobble.y,
+            },
+            spawn_time: now,
+            collected: false,
+            time_scale: TimeScale::default(),
+            last_pos: pos,
+        }
+    }
+
+    pub fn advance(&mut self, delta: f32) {
+        let delta = self.time_scale.apply(delta);
+        self.pos.x += self.vel.x * delta;
+        self.pos.y += self.vel.y * delta;
+    }
+}
@@ -0,0 +1,127 @@
+//! the rainbow easter egg: a pass over each finished frame that cycles the
+//! lines through the colors of the rainbow and leaves trails behind them
+
+use macroquad::prelude::*;
+
+/// how much of the last frame each new one covers up. Lower leaves longer
+/// trails.
+const TRAIL_FADE: f32 = 0.25;
+
+/// keeps every frame in an offscreen target that is faded rather than
+/// cleared between frames, so whatever moves leaves afterimages behind, then
+/// draws that to the screen through a shader that colors the lines by hue
+pub struct Rainbow {
+    target: RenderTarget,
+    material: Material,
+}
+impl Rainbow {
+    pub fn new() -> Result<Rainbow, String> {
+        let material = load_material(
+            VERTEX_SHADER,
+            FRAGMENT_SHADER,
+            MaterialParams {
+                uniforms: vec![("time".to_string(), UniformType::Float1)],
+                ..Default::default()
+            },
+        )
+        .map_err(|err| format!("Rainbow mode isn't supported here: {:?}", err))?;
+        Ok(Rainbow {
+            target: screen_target(),
+            material,
+        })
+    }
+
+    /// starts the trails over on a new target if the window changed size
+    pub fn fit_screen(&mut self) {
+        let texture = self.target.texture;
+        if texture.width() != screen_width() || texture.height() != screen_height() {
+            self.target.delete();
+            self.target = screen_target();
+        }
+    }
+
+    /// draws a frame through the effect. `draw_frame` draws it as usual,
+    /// except for clearing the screen first.
+    pub fn draw(&self, now: f64, draw_frame: impl FnOnce()) {
+        let (width, height) = (screen_width(), screen_height());
+        set_camera(&Camera2D {
+            zoom: vec2(2. / width, 2. / height),
+            target: vec2(width / 2., height / 2.),
+            render_target: Some(self.target),
+            ..Default::default()
+        });
+        let mut fade = LIGHTGRAY;
+        fade.a = TRAIL_FADE;
+        draw_rectangle(0., 0., width, height, fade);
+        draw_frame();
+
+        set_default_camera();
+        gl_use_material(self.material);
+        self.material.set_uniform("time", now as f32);
+        draw_texture_ex(
+            self.target.texture,
+            0.,
+            0.,
+            WHITE,
+            DrawTextureParams {
+                dest_size: Some(vec2(width, height)),
+                ..Default::default()
+            },
+        );
+        gl_use_default_material();
+    }
+}
+impl Drop for Rainbow {
+    fn drop(&mut self) {
+        self.target.delete();
+        self.material.delete();
+    }
+}
+
+fn screen_target() -> RenderTarget {
+    let target = render_target(screen_width() as u32, screen_height() as u32);
+    target.texture.set_filter(FilterMode::Linear);
+    target
+}
+
+const VERTEX_SHADER: &str = "#version 100
+attribute vec3 position;
+attribute vec2 texcoord;
+attribute vec4 color0;
+
+varying lowp vec2 uv;
+varying lowp vec4 color;
+
+uniform mat4 Model;
+uniform mat4 Projection;
+
+void main() {
+    gl_Position = Projection * Model * vec4(position, 1);
+    color = color0 / 255.0;
+    uv = texcoord;
+}
+";
+
+/// colors anything darker than the background by a hue that shifts over
+/// time and across the screen, on black
+const FRAGMENT_SHADER: &str = "#version 100
+precision lowp float;
+
+varying vec4 color;
+varying vec2 uv;
+
+uniform sampler2D Texture;
+uniform float time;
+
+vec3 hue(float h) {
+    return clamp(abs(mod(h * 6.0 + vec3(0.0, 4.0, 2.0), 6.0) - 3.0) - 1.0, 0.0, 1.0);
+}
+
+void main() {
+    vec3 frame = texture2D(Texture, uv).rgb;
+    float brightness = dot(frame, vec3(0.299, 0.587, 0.114));
+    float ink = clamp((0.78 - brightness) / 0.6, 0.0, 1.0);
+    vec3 rainbow = hue(fract(time * 0.2 + (uv.x + uv.y) * 0.5));
+    gl_FragColor = vec4(rainbow * ink, 1.0);
+}
+";
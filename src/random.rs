@@ -0,0 +1,165 @@
+//! the game's random number generators. The world carries its own, saved
+//! and restored with it, so a run plays out the same from the same seed or
+//! snapshot. Everything outside the world, like particles and the title
+//! screen's backdrop, rolls with a separate one seeded from the clock.
+
+use crate::angle::Angle;
+use crate::physics::{field_size, Point, Velocity};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+
+/// how many points `point_clear_of` tries before settling
+const CLEAR_POINT_TRIES: usize = 100;
+
+// the constants of the PCG generator, the same ones macroquad uses
+const PCG_MULTIPLIER: u64 = 6364136223846793005;
+const PCG_INCREMENT: u64 = 1442695040888963407;
+
+thread_local! {
+    static EFFECTS_RNG: RefCell<Rng> = {
+        let seed = macroquad::miniquad::date::now().to_bits();
+        RefCell::new(Rng::new(seed))
+    };
+}
+
+/// rolls with the generator for things that don't need to play out the same
+/// twice, which is seeded from the clock at startup
+pub fn effects<T>(roll: impl FnOnce(&mut Rng) -> T) -> T {
+    EFFECTS_RNG.with(|rng| roll(&mut rng.borrow_mut()))
+}
+
+/// a seed for a new run, different every time
+pub fn fresh_seed() -> u64 {
+    effects(|rng| rng.next() as u64)
+}
+
+/// a random number generator whose whole state is one number, so it can be
+/// saved along with whatever it's rolling for
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Rng {
+    state: u64,
+}
+impl Default for Rng {
+    fn default() -> Rng {
+        Rng::new(0)
+    }
+}
+impl Rng {
+    /// a generator that rolls the same numbers every time for the same seed
+    pub fn new(seed: u64) -> Rng {
+        let mut rng = Rng { state: 0 };
+        rng.next();
+        rng.state = rng.state.wrapping_add(seed);
+        rng.next();
+        rng
+    }
+
+    /// any number that fits in a u32
+    fn next(&mut self) -> u32 {
+        let old = self.state;
+        self.state = old.wrapping_mul(PCG_MULTIPLIER).wrapping_add(PCG_INCREMENT);
+        let xorshifted = (((old >> 18) ^ old) >> 27) as u32;
+        xorshifted.rotate_right((old >> 59) as u32)
+    }
+
+    /// a number from `min` up to `max`
+    pub fn range(&mut self, min: f32, max: f32) -> f32 {
+        let unit = self.next() as f64 / (u32::MAX as f64 + 1.);
+        min + (max - min) * unit as f32
+    }
+
+    /// true with the given probability, from 0. (never) to 1. (always)
+    pub fn chance(&mut self, probability: f32) -> bool {
+        self.range(0., 1.) < probability
+    }
+
+    /// 1. or -1., evenly
+    pub fn sign(&mut self) -> f32 {
+        if self.chance(0.5) {
+            -1.
+        } else {
+            1.
+        }
+    }
+
+    /// a number that is usually close to `mean`, with about two thirds of
+    /// them within `spread` of it, like a bell curve
+    pub fn gaussian(&mut self, mean: f32, spread: f32) -> f32 {
+        // Box-Muller; the first roll can't be 0. or the log blows up
+        let first = self.range(f32::EPSILON, 1.);
+        let second = self.range(0., 1.);
+        let normal = (-2. * first.ln()).sqrt() * (second * std::f32::consts::PI * 2.).cos();
+        mean + normal * spread
+    }
+
+    pub fn angle(&mut self) -> Angle {
+        Angle::from_degrees(self.range(0., 360.))
+    }
+
+    /// a direction with a length of one, evenly likely to point anywhere
+    pub fn unit_vector(&mut self) -> Velocity {
+        let radians = self.angle().radians();
+        Velocity {
+            x: radians.sin(),
+            y: -radians.cos(),
+        }
+    }
+
+    /// a velocity in any direction, with a speed from `min_speed` up to
+    /// `max_speed`
+    pub fn velocity(&mut self, min_speed: f32, max_speed: f32) -> Velocity {
+        let direction = self.unit_vector();
+        let speed = self.range(min_speed, max_speed);
+        Velocity {
+            x: direction.x * speed,
+            y: direction.y * speed,
+        }
+    }
+
+    /// a point anywhere on the playfield
+    pub fn point_on_screen(&mut self) -> Point {
+        let (width, height) = field_size();
+        Point {
+            x: self.range(0., width),
+            y: self.range(0., height),
+        }
+    }
+
+    /// a point on the playfield at least the given distance from each of
+    /// the given points. Gives up after a while on a crowded screen and
+    /// settles for the last point it tried.
+    pub fn point_clear_of(&mut self, avoid: &[(Point, f32)]) -> Point {
+        let mut point = self.point_on_screen();
+        for _ in 0..CLEAR_POINT_TRIES {
+            if avoid.iter().all(|(pos, distance)| point.distance(pos) > *distance) {
+                break;
+            }
+            point = self.point_on_screen();
+        }
+        point
+    }
+}
+
+/// a list of outcomes where some come up more often than others. Each
+/// outcome's chance is its weight over the total of all the weights.
+pub struct WeightedTable<T> {
+    entries: Vec<(f32, T)>,
+}
+impl<T> WeightedTable<T> {
+    pub fn new(entries: Vec<(f32, T)>) -> WeightedTable<T> {
+        WeightedTable { entries }
+    }
+
+    pub fn pick(&self, rng: &mut Rng) -> &T {
+        let total: f32 = self.entries.iter().map(|(weight, _)| weight).sum();
+        let mut roll = rng.range(0., total);
+        for (weight, outcome) in self.entries.iter() {
+            if roll < *weight {
+                return outcome;
+            }
+            roll -= weight;
+        }
+        // rounding can leave the roll just past the last entry
+        &self.entries.last().expect("a weighted table needs at least one entry").1
+    }
+}
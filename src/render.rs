@@ -0,0 +1,517 @@
+//! drawing the game, layer by layer
+
+use crate::asteroid::{Asteroid, SpawnWarning, CORE_SCALE};
+use crate::bullet::{Bullet, Projectile};
+use crate::effects::{
+    CritFlash, FloatingText, ShieldRipple, Shockwave, CRIT_FLASH_TIME, FLOATING_TEXT_TIME, SHIELD_RIPPLE_TIME,
+    SHOCKWAVE_TIME,
+};
+use crate::events::{FeedEntry, FEED_ENTRY_TIME};
+use crate::physics::{field_size, Point};
+use crate::powerup::{PowerUp, POWERUP_LIFETIME, POWERUP_SIZE};
+use crate::satellite::{Satellite, SATELLITE_LIFETIME, SATELLITE_SIZE};
+use crate::saucer::{Saucer, SaucerWarning};
+use crate::ship::{Ship, MAX_ENERGY, MAX_HEAT, SHIP_BASE, SHIP_HEIGHT};
+use crate::turret::{Turret, TURRET_SIZE};
+use crate::weapon::Weapon;
+use macroquad::prelude::*;
+
+const DANGER_RANGE: f32 = 150.;
+/// how much each life held in reserve dulls the danger vignette, so it's at
+/// full strength on the last life
+const SPARE_LIFE_CALM: f32 = 0.2;
+/// the least the vignette is dulled to, however many lives are left
+const MIN_DANGER_SCALE: f32 = 0.4;
+/// degrees a shield ripple reaches around the ring either side of the hit
+const SHIELD_RIPPLE_SPREAD: f32 = 40.;
+
+/// draw layers, from back to front
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Layer {
+    Background,
+    World,
+    Effects,
+    Hud,
+    Overlay,
+}
+
+/// collects a frame's draw calls by layer and draws them back to front, so a
+/// system only has to say which layer it belongs on rather than where its
+/// draw call sits in the main loop. Calls on the same layer keep the order
+/// they were registered in.
+#[derive(Default)]
+pub struct RenderQueue<'a> {
+    draws: Vec<(Layer, Box<dyn FnOnce() + 'a>)>,
+}
+impl<'a> RenderQueue<'a> {
+    pub fn register(&mut self, layer: Layer, draw: impl FnOnce() + 'a) {
+        self.draws.push((layer, Box::new(draw)));
+    }
+
+    pub fn draw(mut self) {
+        self.draws.sort_by_key(|(layer, _)| *layer);
+        for (_, draw) in self.draws {
+            draw();
+        }
+    }
+}
+
+pub fn draw_ship(ship: &Ship, now: f64) {
+    let [v1, v2, v3] = ship.outline().map(|corner| Vec2::new(corner.x, corner.y));
+    if ship.flash.is_flashing(now) {
+        draw_triangle(v1, v2, v3, WHITE);
+    }
+    draw_triangle_lines(v1, v2, v3, 2., BLACK);
+}
+
+/// a ring around the ship while its shield is up
+pub fn draw_shield(ship: &Ship) {
+    if ship.shield {
+        draw_circle_lines(ship.pos.x, ship.pos.y, SHIP_HEIGHT * 0.8, 2., SKYBLUE);
+    }
+}
+
+/// an arc on the shield ring on the side a hit came from, swelling outward
+/// and fading
+pub fn draw_shield_ripple(ripple: &ShieldRipple, ship: &Ship, now: f64) {
+    let progress = ((now - ripple.start_time) / SHIELD_RIPPLE_TIME) as f32;
+    let radius = SHIP_HEIGHT * (0.8 + progress * 0.4);
+    let color = Color::new(0.4, 0.75, 1., 1. - progress);
+    let segments = 8;
+    let point = |step: usize| {
+        let mut angle = ripple.angle;
+        angle.rotate(SHIELD_RIPPLE_SPREAD * (step as f32 / segments as f32 * 2. - 1.));
+        let radians = angle.radians();
+        (ship.pos.x + radians.sin() * radius, ship.pos.y - radians.cos() * radius)
+    };
+    for step in 0..segments {
+        let ((x1, y1), (x2, y2)) = (point(step), point(step + 1));
+        draw_line(x1, y1, x2, y2, 3. * (1. - progress) + 1., color);
+    }
+}
+
+/// the ship's energy meter, under the wave counter in the top right corner.
+/// It turns red once there isn't enough left to take another hit.
+pub fn draw_energy(ship: &Ship) {
+    let width = 60.;
+    let x = screen_width() - 10. - width;
+    let color = if ship.has_energy_for_hit() { SKYBLUE } else { RED };
+    draw_rectangle(x, 58., width * ship.energy / MAX_ENERGY, 6., color);
+    draw_rectangle_lines(x, 58., width, 6., 1., DARKGRAY);
+}
+
+/// a dot for each smart bomb left, under the energy meter
+pub fn draw_bombs(bombs: u8) {
+    for bomb in 0..bombs {
+        let x = screen_width() - 14. - bomb as f32 * 12.;
+        draw_circle(x, 76., 4., DARKGRAY);
+    }
+}
+
+/// how long the ship's timed power-ups have left, in the bottom left corner
+pub fn draw_powerup_timers(ship: &Ship, now: f64) {
+    let timers = [
+        ("Rapid fire", ship.rapid_fire_left(now)),
+        ("Spread shot", ship.spread_left(now)),
+    ];
+    let active = timers.iter().filter(|(_, left)| *left > 0.);
+    for (line, (name, left)) in active.enumerate() {
+        let text = format!("{} {:.0}s", name, left.ceil());
+        draw_text(&text, 10., screen_height() - 10. - line as f32 * 20., 18., DARKGRAY);
+    }
+}
+
+/// the ship's weapon and its ammo, in the bottom right corner, with the
+/// laser's heat over it while the laser is picked
+pub fn draw_weapon(ship: &Ship) {
+    let text = match ship.ammo(ship.weapon) {
+        Some(shots) => format!("{} {}", ship.weapon.name(), shots),
+        None => ship.weapon.name().to_string(),
+    };
+    let size = measure_text(&text, None, 18, 1.0);
+    draw_text(&text, screen_width() - 10. - size.width, screen_height() - 10., 18., DARKGRAY);
+
+    if ship.weapon == Weapon::Laser {
+        let width = 60.;
+        let x = screen_width() - 10. - width;
+        let y = screen_height() - 36.;
+        let color = if ship.overheated { RED } else { ORANGE };
+        draw_rectangle(x, y, width * ship.heat / MAX_HEAT, 6., color);
+        draw_rectangle_lines(x, y, width, 6., 1., DARKGRAY);
+    }
+}
+
+/// the laser beam out of the ship's nose, while it's firing
+pub fn draw_laser(ship: &Ship) {
+    if !ship.firing_laser {
+        return;
+    }
+    let nose = ship.outline()[0];
+    let radians = ship.rotation.radians();
+    let end_x = nose.x + radians.sin() * ship.laser_reach;
+    let end_y = nose.y - radians.cos() * ship.laser_reach;
+    draw_line(nose.x, nose.y, end_x, end_y, 2., RED);
+}
+
+/// a flame out the back of the ship while it thrusts, flickering in length
+pub fn draw_exhaust(ship: &Ship, now: f64) {
+    if !ship.thrusting {
+        return;
+    }
+    let rotation = ship.rotation.radians();
+    let flicker = 0.7 + ((now * 40.).sin() * 0.5 + 0.5) as f32 * 0.6;
+    let length = SHIP_HEIGHT * 0.6 * flicker;
+
+    // the flame hangs off the middle of the ship's base
+    let base_x = ship.pos.x - rotation.sin() * SHIP_HEIGHT / 2.;
+    let base_y = ship.pos.y + rotation.cos() * SHIP_HEIGHT / 2.;
+    let v1 = Vec2::new(base_x - rotation.sin() * length, base_y + rotation.cos() * length);
+    let v2 = Vec2::new(
+        base_x - rotation.cos() * SHIP_BASE / 4.,
+        base_y - rotation.sin() * SHIP_BASE / 4.,
+    );
+    let v3 = Vec2::new(
+        base_x + rotation.cos() * SHIP_BASE / 4.,
+        base_y + rotation.sin() * SHIP_BASE / 4.,
+    );
+    draw_triangle(v1, v2, v3, ORANGE);
+}
+
+/// a row of small ships in the top right corner, one per life left. They
+/// flash while `flashing`, to show a life was just won, and turn red as
+/// `pulse` goes from 0. to 1. with the low lives heartbeat.
+pub fn draw_lives(lives: u8, flashing: bool, pulse: f32) {
+    let color = if flashing && (get_time() * 8.) as i64 % 2 == 0 {
+        ORANGE
+    } else {
+        Color::new(
+            DARKGRAY.r + (RED.r - DARKGRAY.r) * pulse,
+            DARKGRAY.g + (RED.g - DARKGRAY.g) * pulse,
+            DARKGRAY.b + (RED.b - DARKGRAY.b) * pulse,
+            1.,
+        )
+    };
+    for life in 0..lives {
+        let x = screen_width() - 20. - life as f32 * 20.;
+        draw_triangle_lines(
+            Vec2::new(x, 12.),
+            Vec2::new(x - SHIP_BASE / 4., 12. + SHIP_HEIGHT / 2.),
+            Vec2::new(x + SHIP_BASE / 4., 12. + SHIP_HEIGHT / 2.),
+            1.5,
+            color,
+        );
+    }
+}
+
+/// the score, across the top of the screen
+pub fn draw_score(score: u32) {
+    draw_centered_text(&score.to_string(), 30., 28.);
+}
+
+/// the current wave, under the lives in the top right corner
+pub fn draw_wave_counter(wave: u32) {
+    let text = format!("Wave {}", wave);
+    let size = measure_text(&text, None, 18, 1.0);
+    draw_text(&text, screen_width() - 10. - size.width, 50., 18., DARKGRAY);
+}
+
+/// announces a wave as its asteroids come in
+pub fn draw_wave_banner(wave: u32) {
+    draw_centered_text(&format!("Wave {}", wave), screen_height() / 3., 40.);
+}
+
+pub fn draw_asteroid(asteroid: &Asteroid, now: f64) {
+    if asteroid.flash.is_flashing(now) {
+        draw_poly(
+            asteroid.pos.x,
+            asteroid.pos.y,
+            asteroid.sides,
+            asteroid.size,
+            asteroid.rotation.degrees(),
+            WHITE,
+        );
+    }
+    // outermost layer first, each one nested inside the last
+    let mut size = asteroid.size;
+    for material in asteroid.layers.iter().rev() {
+        draw_poly_lines(
+            asteroid.pos.x,
+            asteroid.pos.y,
+            asteroid.sides,
+            size,
+            asteroid.rotation.degrees(),
+            2.,
+            material.color(),
+        );
+        size *= CORE_SCALE;
+    }
+    draw_cracks(asteroid);
+}
+
+/// draws one crack per hit the asteroid has taken. Each crack runs from a
+/// corner of the outline toward the center, bending halfway in, and rotates
+/// with the asteroid. The cracks reach further in the closer the asteroid is
+/// to breaking, so a tough one's first crack is shorter than its last.
+fn draw_cracks(asteroid: &Asteroid) {
+    let damage = asteroid.damage();
+    let breaking = damage as f32 / (asteroid.max_hp - 1).max(1) as f32;
+    let reach = 1. - 0.75 * breaking;
+    for crack in 0..damage {
+        // spread the cracks around the outline rather than stacking them
+        let corner = (crack * 2) % asteroid.sides;
+        let start = asteroid.vertex(corner, 1.);
+        let bend = asteroid.vertex(corner, (1. + reach) / 2.);
+        let bend = Point {
+            x: bend.x + (start.y - asteroid.pos.y) * 0.15,
+            y: bend.y - (start.x - asteroid.pos.x) * 0.15,
+        };
+        let end = asteroid.vertex(corner, reach);
+
+        draw_line(start.x, start.y, bend.x, bend.y, 1.5, BLACK);
+        draw_line(bend.x, bend.y, end.x, end.y, 1.5, BLACK);
+    }
+}
+
+pub fn draw_satellite(satellite: &Satellite) {
+    draw_poly(satellite.pos.x, satellite.pos.y, 4, SATELLITE_SIZE, 45., DARKBLUE);
+}
+
+/// draws a satellite drifting free, blinking when it is about to drift off
+/// for good
+pub fn draw_loose_satellite(satellite: &Satellite, now: f64) {
+    let time_left = satellite.released_time + SATELLITE_LIFETIME - now;
+    if time_left > 2. || time_left % 0.3 > 0.15 {
+        draw_satellite(satellite);
+    }
+}
+
+/// the classic saucer: a wide hull with a dome on top
+pub fn draw_saucer(saucer: &Saucer) {
+    let Point { x, y } = saucer.pos;
+    let (half, rim) = (saucer.size(), saucer.size() * 0.35);
+    let hull = [
+        (x - half, y),
+        (x - half * 0.5, y - rim),
+        (x + half * 0.5, y - rim),
+        (x + half, y),
+        (x + half * 0.5, y + rim),
+        (x - half * 0.5, y + rim),
+    ];
+    for (corner, (x1, y1)) in hull.iter().enumerate() {
+        let (x2, y2) = hull[(corner + 1) % hull.len()];
+        draw_line(*x1, *y1, x2, y2, 2., BLACK);
+    }
+    draw_line(x - half, y, x + half, y, 1., BLACK);
+    draw_rectangle_lines(x - half * 0.3, y - rim * 2., half * 0.6, rim, 2., BLACK);
+}
+
+/// a blinking arrow just inside the edge a saucer is about to fly in by,
+/// pointing the way it will go
+pub fn draw_saucer_warning(warning: &SaucerWarning) {
+    let (width, _) = field_size();
+    let saucer = &warning.saucer;
+    let heading = saucer.vel.x.signum();
+    let x = saucer.pos.x.clamp(12., width - 12.);
+    let y = saucer.pos.y;
+    let mut color = RED;
+    color.a = warning.blink.value();
+    draw_triangle(
+        vec2(x + heading * 10., y),
+        vec2(x - heading * 6., y - 9.),
+        vec2(x - heading * 6., y + 9.),
+        color,
+    );
+    let text_size = measure_text("!", None, 30, 1.0);
+    draw_text(
+        "!",
+        x + heading * 18. - text_size.width / 2.,
+        y + text_size.height / 2.,
+        30.,
+        color,
+    );
+}
+
+/// a saucer's or turret's shot, in red so it stands out from the ship's own
+pub fn draw_enemy_shot(shot: &Bullet) {
+    match shot.projectile {
+        Projectile::TurretShot => draw_circle(shot.pos.x, shot.pos.y, 3.5, MAROON),
+        _ => draw_circle(shot.pos.x, shot.pos.y, 2.5, RED),
+    }
+}
+
+/// a faint dotted line along where something is headed, fading out toward
+/// the end
+pub fn draw_predicted_path(path: &[Point], color: Color) {
+    for (step, point) in path.iter().enumerate() {
+        let fade = 1. - step as f32 / path.len() as f32;
+        draw_circle(point.x, point.y, 1.5, Color::new(color.r, color.g, color.b, 0.5 * fade));
+    }
+}
+
+/// a turret at `pos` on its asteroid, with its barrel pointing where it's
+/// aiming
+pub fn draw_turret(pos: Point, turret: &Turret) {
+    let radians = turret.aim.radians();
+    let length = TURRET_SIZE * 2.2;
+    let end_x = pos.x + radians.sin() * length;
+    let end_y = pos.y - radians.cos() * length;
+    draw_line(pos.x, pos.y, end_x, end_y, 2.5, MAROON);
+    draw_circle(pos.x, pos.y, TURRET_SIZE, MAROON);
+}
+
+/// a short dart pointing the way the missile is flying
+pub fn draw_missile(missile: &Bullet) {
+    let speed = missile.vel.x.hypot(missile.vel.y).max(1.);
+    let length = 8.;
+    let tail_x = missile.pos.x - missile.vel.x / speed * length;
+    let tail_y = missile.pos.y - missile.vel.y / speed * length;
+    draw_line(tail_x, tail_y, missile.pos.x, missile.pos.y, 2., DARKGRAY);
+    draw_circle(missile.pos.x, missile.pos.y, 2., RED);
+}
+
+/// a closed shape through `points`
+pub fn draw_outline(points: &[Point], color: Color) {
+    for (index, point) in points.iter().enumerate() {
+        let next = points[(index + 1) % points.len()];
+        draw_line(point.x, point.y, next.x, next.y, 1., color);
+    }
+}
+
+/// a power-up's letter in a ring, blinking when it's about to disappear
+pub fn draw_powerup(powerup: &PowerUp, now: f64) {
+    let time_left = powerup.spawn_time + POWERUP_LIFETIME - now;
+    if time_left < 2. && time_left % 0.3 < 0.15 {
+        return;
+    }
+    draw_circle_lines(powerup.pos.x, powerup.pos.y, POWERUP_SIZE, 2., SKYBLUE);
+    let letter = powerup.kind.letter();
+    let size = measure_text(letter, None, 16, 1.0);
+    draw_text(
+        letter,
+        powerup.pos.x - size.width / 2.,
+        powerup.pos.y + size.height / 2.,
+        16.,
+        SKYBLUE,
+    );
+}
+
+pub fn draw_spawn_warning(warning: &SpawnWarning) {
+    let asteroid = &warning.asteroid;
+    let mut color = RED;
+    color.a = warning.blink.value();
+    draw_circle_lines(
+        asteroid.pos.x,
+        asteroid.pos.y,
+        asteroid.size * warning.pulse.value(),
+        2.,
+        color,
+    );
+    let text_size = measure_text("!", None, 30, 1.0);
+    draw_text(
+        "!",
+        asteroid.pos.x - text_size.width / 2.,
+        asteroid.pos.y + text_size.height / 2.,
+        30.,
+        color,
+    );
+}
+
+/// a smart bomb's ring, spreading out and fading
+pub fn draw_shockwave(wave: &Shockwave, now: f64) {
+    let progress = ((now - wave.start_time) / SHOCKWAVE_TIME) as f32;
+    let color = Color::new(1., 0.6, 0., 1. - progress);
+    draw_circle_lines(wave.pos.x, wave.pos.y, wave.radius * progress, 4., color);
+}
+
+pub fn draw_crit_flash(flash: &CritFlash, now: f64) {
+    let progress = ((now - flash.start_time) / CRIT_FLASH_TIME) as f32;
+    let radius = 10. + progress * 30.;
+    let color = Color::new(1., 0.6, 0., 1. - progress);
+    for ray in 0..8 {
+        let angle = ray as f32 / 8. * std::f32::consts::PI * 2.;
+        draw_line(
+            flash.pos.x + angle.cos() * radius * 0.4,
+            flash.pos.y + angle.sin() * radius * 0.4,
+            flash.pos.x + angle.cos() * radius,
+            flash.pos.y + angle.sin() * radius,
+            3.,
+            color,
+        );
+    }
+}
+
+pub fn draw_floating_text(floating: &FloatingText, now: f64) {
+    let progress = ((now - floating.start_time) / FLOATING_TEXT_TIME) as f32;
+    let mut color = floating.color;
+    color.a = 1. - progress;
+    draw_text(
+        &floating.text,
+        floating.pos.x,
+        floating.pos.y - progress * 30.,
+        24.,
+        color,
+    );
+}
+
+pub fn draw_event_feed(feed: &[FeedEntry], now: f64) {
+    for (line, entry) in feed.iter().enumerate() {
+        let age = ((now - entry.start_time) / FEED_ENTRY_TIME) as f32;
+        draw_text(
+            &entry.text,
+            10.,
+            20. + line as f32 * 18.,
+            18.,
+            Color::new(0.2, 0.2, 0.2, 1. - age * age),
+        );
+    }
+}
+
+/// how much danger the ship is in, from 0. (safe) to 1. (about to be hit
+/// on the last life), based on the gap between it and the closest asteroid
+/// and how many lives are left to lose
+pub fn danger_level(ship: &Ship, asteroids: &[Asteroid], lives: u8) -> f32 {
+    let closest = asteroids
+        .iter()
+        .map(|asteroid| asteroid.pos.distance(&ship.pos) - asteroid.size)
+        .fold(f32::MAX, f32::min);
+    let spare_lives = lives.saturating_sub(1) as f32;
+    let scale = (1. - spare_lives * SPARE_LIFE_CALM).max(MIN_DANGER_SCALE);
+
+    (1. - (closest / DANGER_RANGE).clamp(0., 1.)) * scale
+}
+
+/// darkens the edges of the screen in red. Bands are stacked from the edge
+/// inward, so the color is strongest at the border and fades toward the
+/// center.
+pub fn draw_danger_vignette(intensity: f32) {
+    if intensity <= 0.01 {
+        return;
+    }
+
+    let bands = 12;
+    let band_size = screen_width().min(screen_height()) * 0.15 / bands as f32;
+    for band in 0..bands {
+        let falloff = 1. - band as f32 / bands as f32;
+        let color = Color::new(0.8, 0., 0., intensity * falloff * falloff * 0.15);
+        let inset = band as f32 * band_size;
+        let width = screen_width() - inset * 2.;
+        let height = screen_height() - inset * 2.;
+
+        draw_rectangle(inset, inset, width, band_size, color);
+        draw_rectangle(inset, inset + height - band_size, width, band_size, color);
+        draw_rectangle(inset, inset + band_size, band_size, height - band_size * 2., color);
+        draw_rectangle(
+            inset + width - band_size,
+            inset + band_size,
+            band_size,
+            height - band_size * 2.,
+            color,
+        );
+    }
+}
+
+/// draws a line of text centered horizontally on the screen
+pub fn draw_centered_text(text: &str, y: f32, font_size: f32) {
+    let size = measure_text(text, None, font_size as _, 1.0);
+    draw_text(text, screen_width() / 2. - size.width / 2., y, font_size, DARKGRAY);
+}
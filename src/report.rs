@@ -0,0 +1,242 @@
+//! a summary of each run, built from its events, for the game over screen and
+//! for exporting
+
+use crate::asteroid::size_name;
+use crate::events::GameEvent;
+use crate::storage::Storage;
+use crate::world::World;
+use serde::Serialize;
+
+/// statistics for a single wave of asteroids
+#[derive(Debug, Default, Serialize)]
+pub struct WaveStats {
+    pub wave: u32,
+    pub shots_fired: u32,
+    pub asteroids_cracked: u32,
+    pub shells_broken: u32,
+    pub asteroids_destroyed: u32,
+    pub satellites_collected: u32,
+    pub critical_hits: u32,
+    pub saucers_destroyed: u32,
+    pub ship_destroyed: bool,
+    /// seconds the wave took to clear, if it was
+    pub clear_secs: Option<f64>,
+    pub speed_bonus: u32,
+}
+
+/// a summary of the last game, built up from game events so it can be
+/// exported for a closer look outside the game
+#[derive(Debug, Serialize)]
+pub struct RunReport {
+    pub duration_secs: f64,
+    /// the world's score, copied over after every step
+    pub score: u32,
+    /// how the run was set up, to pass on to someone else
+    pub share_code: String,
+    pub waves: Vec<WaveStats>,
+    /// every event of the run with the seconds since it started
+    #[serde(skip)]
+    pub log: Vec<(f64, GameEvent)>,
+}
+impl RunReport {
+    pub fn new(share_code: String) -> RunReport {
+        RunReport {
+            duration_secs: 0.,
+            score: 0,
+            share_code,
+            waves: vec![WaveStats {
+                wave: 1,
+                ..WaveStats::default()
+            }],
+            log: Vec::new(),
+        }
+    }
+
+    /// a fresh report for a run picked up partway through from a saved
+    /// world, starting from its score and wave
+    pub fn resume(share_code: String, world: &World) -> RunReport {
+        RunReport {
+            duration_secs: world.time - world.run_start,
+            score: world.score,
+            share_code,
+            waves: vec![WaveStats {
+                wave: world.wave,
+                ..WaveStats::default()
+            }],
+            log: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, time: f64, event: &GameEvent) {
+        self.log.push((time, *event));
+
+        if let GameEvent::WaveStarted(wave) = event {
+            self.waves.push(WaveStats {
+                wave: *wave,
+                ..WaveStats::default()
+            });
+        }
+
+        let wave = self.waves.last_mut().expect("a run always has a wave");
+        match event {
+            GameEvent::ShotFired => wave.shots_fired += 1,
+            GameEvent::AsteroidCracked => wave.asteroids_cracked += 1,
+            GameEvent::ShellBroken { .. } => wave.shells_broken += 1,
+            GameEvent::SatelliteCollected => wave.satellites_collected += 1,
+            GameEvent::AsteroidDestroyed { .. } => wave.asteroids_destroyed += 1,
+            GameEvent::CriticalHit => wave.critical_hits += 1,
+            GameEvent::SaucerDestroyed(_) => wave.saucers_destroyed += 1,
+            GameEvent::WaveCleared { secs, bonus, .. } => {
+                wave.clear_secs = Some(*secs);
+                wave.speed_bonus = *bonus;
+            }
+            GameEvent::ShipDestroyed { .. }
+            | GameEvent::ShipDestroyedBySaucer { .. }
+            | GameEvent::ShipDestroyedByTurret { .. } => wave.ship_destroyed = true,
+            GameEvent::AsteroidsIncoming(_)
+            | GameEvent::SaucerArrived
+            | GameEvent::PowerUpCollected(_)
+            | GameEvent::ShieldAbsorbed
+            | GameEvent::WaveStarted(_)
+            | GameEvent::ExtraLife
+            | GameEvent::GameSaved
+            | GameEvent::GameLoaded => {}
+        }
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// one row per wave, for spreadsheets
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from(
+            "wave,shots_fired,asteroids_cracked,shells_broken,asteroids_destroyed,satellites_collected,critical_hits,saucers_destroyed,ship_destroyed,clear_secs,speed_bonus\n",
+        );
+        for wave in self.waves.iter() {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{},{},{},{},{}\n",
+                wave.wave,
+                wave.shots_fired,
+                wave.asteroids_cracked,
+                wave.shells_broken,
+                wave.asteroids_destroyed,
+                wave.satellites_collected,
+                wave.critical_hits,
+                wave.saucers_destroyed,
+                wave.ship_destroyed,
+                wave.clear_secs.map_or(String::new(), |secs| format!("{:.1}", secs)),
+                wave.speed_bonus,
+            ));
+        }
+        csv
+    }
+}
+
+/// looks over a run's event log for a few things worth telling the player
+pub fn analyze_run(log: &[(f64, GameEvent)], duration: f64) -> Vec<String> {
+    let mut insights = Vec::new();
+
+    for (_, event) in log.iter() {
+        match event {
+            GameEvent::ShipDestroyed { sides, from } => insights.push(format!(
+                "Destroyed by a {} asteroid {}",
+                size_name(*sides),
+                from.describe()
+            )),
+            GameEvent::ShipDestroyedBySaucer { from } => {
+                insights.push(format!("Shot down by a saucer {}", from.describe()))
+            }
+            GameEvent::ShipDestroyedByTurret { from } => {
+                insights.push(format!("Shot down by an asteroid turret {}", from.describe()))
+            }
+            _ => {}
+        }
+    }
+
+    // compare accuracy over each half of the run
+    let halfway = duration / 2.;
+    let accuracy = |first_half: bool| {
+        let in_half = |time: f64| (time < halfway) == first_half;
+        let shots = log
+            .iter()
+            .filter(|(time, event)| in_half(*time) && matches!(event, GameEvent::ShotFired))
+            .count();
+        let hits = log
+            .iter()
+            .filter(|(time, event)| in_half(*time) && event.is_hit())
+            .count();
+        (shots > 0).then(|| hits as f32 / shots as f32 * 100.)
+    };
+    match (accuracy(true), accuracy(false)) {
+        (Some(first), Some(second)) if second + 10. < first => insights.push(format!(
+            "Accuracy dipped from {:.0}% to {:.0}% in the second half",
+            first, second
+        )),
+        (Some(first), Some(second)) if first + 10. < second => insights.push(format!(
+            "Accuracy climbed from {:.0}% to {:.0}% as the run went on",
+            first, second
+        )),
+        (Some(first), Some(second)) => insights.push(format!(
+            "Steady aim: {:.0}% accuracy overall",
+            (first + second) / 2.
+        )),
+        _ => {}
+    }
+
+    // the longest stretch without landing a hit
+    let mut last_hit = 0.;
+    let mut longest_drought: f64 = 0.;
+    for (time, event) in log.iter() {
+        if event.is_hit() {
+            longest_drought = longest_drought.max(time - last_hit);
+            last_hit = *time;
+        }
+    }
+    longest_drought = longest_drought.max(duration - last_hit);
+    if longest_drought > 10. {
+        insights.push(format!(
+            "Longest stretch without a hit: {:.0} seconds",
+            longest_drought
+        ));
+    }
+
+    let fastest = log
+        .iter()
+        .filter_map(|(_, event)| match event {
+            GameEvent::WaveCleared { wave, secs, .. } => Some((*wave, *secs)),
+            _ => None,
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b));
+    if let Some((wave, secs)) = fastest {
+        insights.push(format!("Fastest wave: wave {} in {:.0} seconds", wave, secs));
+    }
+
+    let crits = log
+        .iter()
+        .filter(|(_, event)| matches!(event, GameEvent::CriticalHit))
+        .count();
+    if crits > 0 {
+        insights.push(format!("Landed {} critical hits", crits));
+    }
+
+    insights
+}
+
+/// writes the report out with the game's other files and describes how it
+/// went
+pub fn export_report(report: &RunReport, json: bool, storage: &dyn Storage) -> String {
+    let (name, contents) = if json {
+        match report.to_json() {
+            Ok(contents) => ("run_report.json", contents),
+            Err(err) => return format!("Couldn't export the run: {}", err),
+        }
+    } else {
+        ("run_report.csv", report.to_csv())
+    };
+
+    match storage.write(name, contents.as_bytes()) {
+        Ok(()) => format!("Run exported to {}", storage.path(name).display()),
+        Err(err) => err,
+    }
+}
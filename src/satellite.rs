@@ -0,0 +1,75 @@
+//! satellites that orbit asteroids and can be collected once set loose
+
+use crate::physics::{Point, TimeScale, Velocity};
+use crate::random::Rng;
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub const SATELLITE_CHANCE: f32 = 0.3;
+pub const SATELLITE_SIZE: f32 = 5.;
+pub const SATELLITE_LIFETIME: f64 = 8.;
+pub const SATELLITE_CRIT_BONUS: f32 = 0.02;
+pub const MAX_CRIT_CHANCE: f32 = 0.5;
+
+/// a small satellite. While attached to an asteroid it orbits it; once the
+/// asteroid is destroyed it drifts free until it is collected or expires.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Satellite {
+    pub pos: Point,
+    pub vel: Velocity,
+    pub orbit_radius: f32,
+    /// degrees around the parent asteroid
+    pub orbit_angle: f32,
+    /// degrees per second
+    pub orbit_speed: f32,
+    pub released_time: f64,
+    pub collected: bool,
+    #[serde(default)]
+    pub time_scale: TimeScale,
+    /// where the satellite was at the start of the last simulation step
+    #[serde(skip)]
+    pub last_pos: Point,
+}
+impl Satellite {
+    pub fn new(orbit_radius: f32, rng: &mut Rng) -> Satellite {
+        Satellite {
+            pos: Point::default(),
+            vel: Velocity::default(),
+            orbit_radius,
+            orbit_angle: rng.range(0., 360.),
+            orbit_speed: rng.range(60., 120.) * rng.sign(),
+            released_time: 0.,
+            collected: false,
+            time_scale: TimeScale::default(),
+            last_pos: Point::default(),
+        }
+    }
+
+    /// moves the satellite along its orbit around `center`
+    pub fn orbit(&mut self, center: Point, delta: f32) {
+        self.orbit_angle += self.orbit_speed * self.time_scale.apply(delta);
+        let radians = self.orbit_angle.to_radians();
+        self.pos = Point {
+            x: center.x + radians.cos() * self.orbit_radius,
+            y: center.y + radians.sin() * self.orbit_radius,
+        };
+    }
+
+    /// detaches the satellite, flinging it off along its orbit
+    pub fn release(mut self, parent_vel: Velocity, now: f64) -> Satellite {
+        let radians = self.orbit_angle.to_radians();
+        let speed = self.orbit_speed.to_radians() * self.orbit_radius;
+        self.vel = Velocity {
+            x: parent_vel.x - radians.sin() * speed,
+            y: parent_vel.y + radians.cos() * speed,
+        };
+        self.released_time = now;
+        self
+    }
+
+    pub fn advance(&mut self, delta: f32) {
+        let delta = self.time_scale.apply(delta);
+        self.pos.x += self.vel.x * delta;
+        self.pos.y += self.vel.y * delta;
+    }
+}
@@ -0,0 +1,234 @@
+//! flying saucers that cross the field shooting at the ship
+
+use crate::angle::Angle;
+use crate::animation::Animation;
+use crate::bullet::{Bullet, Projectile, SAUCER_SHOT_SPEED};
+use crate::physics::{field_size, Point, TimeScale, Velocity};
+use crate::random::Rng;
+use crate::settings::Edges;
+use serde::{Deserialize, Serialize};
+
+/// seconds into a wave before the first saucer shows up
+pub const SAUCER_DELAY: f64 = 15.;
+/// seconds between one saucer leaving and the next arriving
+pub const SAUCER_INTERVAL: f64 = 25.;
+/// seconds a saucer is warned of before it flies in
+pub const SAUCER_WARNING_TIME: f64 = 1.5;
+/// seconds between changes of vertical heading
+const SAUCER_TURN_INTERVAL: f64 = 2.;
+/// the score from which small saucers start turning up
+const SMALL_SAUCER_SCORE: u32 = 10_000;
+/// the score from which every saucer is a small one
+const ALWAYS_SMALL_SAUCER_SCORE: u32 = 40_000;
+/// the chance of a small saucer once they start turning up
+const FIRST_SMALL_SAUCER_CHANCE: f32 = 0.25;
+
+/// how a kind of saucer flies and shoots
+pub struct Behavior {
+    /// half the saucer's width
+    pub size: f32,
+    /// pixels per second across the screen
+    speed: f32,
+    /// seconds between shots
+    fire_interval: f64,
+    /// the most a shot can miss its aim by, in degrees either way
+    aim_error: f32,
+    /// whether it aims where the ship is headed rather than where it is
+    leads_shots: bool,
+}
+
+/// the big, slow saucer that sprays shots roughly at the ship
+const LARGE: Behavior = Behavior {
+    size: 16.,
+    speed: 80.,
+    fire_interval: 1.5,
+    aim_error: 12.,
+    leads_shots: false,
+};
+/// the small saucer that shows up at high scores: quicker, harder to hit,
+/// and it shoots to where the ship will be
+const SMALL: Behavior = Behavior {
+    size: 9.,
+    speed: 110.,
+    fire_interval: 1.,
+    aim_error: 2.,
+    leads_shots: true,
+};
+
+/// the two kinds of saucer, as in the arcade game
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum SaucerKind {
+    #[default]
+    Large,
+    Small,
+}
+impl SaucerKind {
+    /// the kind of the next saucer for a run with this score. Small ones
+    /// start turning up past `SMALL_SAUCER_SCORE`, more often as the score
+    /// climbs, until they're all that come.
+    pub fn roll(score: u32, rng: &mut Rng) -> SaucerKind {
+        let progress = score.saturating_sub(SMALL_SAUCER_SCORE) as f32
+            / (ALWAYS_SMALL_SAUCER_SCORE - SMALL_SAUCER_SCORE) as f32;
+        if score >= SMALL_SAUCER_SCORE && rng.chance(progress.max(FIRST_SMALL_SAUCER_CHANCE)) {
+            SaucerKind::Small
+        } else {
+            SaucerKind::Large
+        }
+    }
+
+    pub fn behavior(&self) -> &'static Behavior {
+        match self {
+            SaucerKind::Large => &LARGE,
+            SaucerKind::Small => &SMALL,
+        }
+    }
+}
+
+/// a saucer crossing from one side of the screen to the other, zigzagging
+/// up and down and firing at the ship as it goes. It leaves once it reaches
+/// the far side.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Saucer {
+    pub pos: Point,
+    pub vel: Velocity,
+    pub last_shot: f64,
+    pub next_turn: f64,
+    pub collided: bool,
+    #[serde(default)]
+    pub kind: SaucerKind,
+    #[serde(default)]
+    pub time_scale: TimeScale,
+    /// where the saucer was at the start of the last simulation step
+    #[serde(skip)]
+    pub last_pos: Point,
+}
+impl Saucer {
+    /// a saucer just off the left or right edge, heading across
+    pub fn enter(kind: SaucerKind, now: f64, rng: &mut Rng) -> Saucer {
+        let (width, height) = field_size();
+        let Behavior { size, speed, .. } = *kind.behavior();
+        let from_left = rng.chance(0.5);
+        let pos = Point {
+            x: if from_left { -size } else { width + size },
+            y: rng.range(height * 0.1, height * 0.9),
+        };
+        Saucer {
+            pos,
+            vel: Velocity {
+                x: if from_left { speed } else { -speed },
+                y: 0.,
+            },
+            last_shot: now,
+            next_turn: now + SAUCER_TURN_INTERVAL,
+            collided: false,
+            kind,
+            time_scale: TimeScale::default(),
+            last_pos: pos,
+        }
+    }
+
+    /// half the saucer's width
+    pub fn size(&self) -> f32 {
+        self.kind.behavior().size
+    }
+
+    /// moves the saucer on by `delta` seconds. It leaves by the sides, so
+    /// only the top and bottom edges hold it in.
+    pub fn advance(&mut self, delta: f32, edges: Edges, now: f64, rng: &mut Rng) {
+        if now >= self.next_turn {
+            // veer up or down, or level out
+            self.vel.y = self.kind.behavior().speed * 0.6 * rng.range(-1., 2.).floor();
+            self.next_turn = now + SAUCER_TURN_INTERVAL;
+        }
+        let delta = self.time_scale.apply(delta);
+        self.pos.x += self.vel.x * delta;
+        self.pos.y += self.vel.y * delta;
+
+        let (_, height) = field_size();
+        let size = self.size();
+        match edges {
+            Edges::Wrap if self.pos.y > height => self.pos.y = 0.,
+            Edges::Wrap if self.pos.y < 0. => self.pos.y = height,
+            Edges::Walls if self.pos.y < size || self.pos.y > height - size => {
+                self.pos.y = self.pos.y.clamp(size, height - size);
+                self.vel.y = -self.vel.y;
+            }
+            _ => {}
+        }
+    }
+
+    /// whether the saucer has flown off the side it was heading for
+    pub fn has_crossed(&self) -> bool {
+        (self.vel.x > 0. && self.pos.x > field_size().0 + self.size())
+            || (self.vel.x < 0. && self.pos.x < -self.size())
+    }
+
+    /// a shot at a target at `target` moving at `target_vel`, if the saucer
+    /// is due to fire. Its aim is off by a little, so a moving ship can
+    /// dodge.
+    pub fn fire(&mut self, target: Point, target_vel: Velocity, now: f64, rng: &mut Rng) -> Option<Bullet> {
+        let behavior = self.kind.behavior();
+        if now - self.last_shot < behavior.fire_interval {
+            return None;
+        }
+        self.last_shot = now;
+        let mut aim_at = target;
+        if behavior.leads_shots {
+            // where the target will be by the time a shot covers the
+            // distance to it now, which is near enough at these speeds
+            let flight = self.pos.distance(&target) / SAUCER_SHOT_SPEED;
+            aim_at.x += target_vel.x * flight;
+            aim_at.y += target_vel.y * flight;
+        }
+        let mut aim = Angle::towards(self.pos, aim_at);
+        aim.rotate(rng.range(-behavior.aim_error, behavior.aim_error));
+        Some(Bullet::aimed(self.pos, aim, Projectile::SaucerShot, self.time_scale, now))
+    }
+}
+
+/// a saucer on its way in. A marker blinks at the edge it will come in by
+/// until `arrival`, so the player isn't caught off guard.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaucerWarning {
+    pub saucer: Saucer,
+    pub arrival: f64,
+    /// opacity of the marker, blinking a few times a second
+    pub blink: Animation,
+}
+impl SaucerWarning {
+    pub fn new(kind: SaucerKind, now: f64, rng: &mut Rng) -> SaucerWarning {
+        let arrival = now + SAUCER_WARNING_TIME;
+        SaucerWarning {
+            saucer: Saucer::enter(kind, arrival, rng),
+            arrival,
+            blink: Animation::new(vec![(0., 1.), (0.15, 1.), (0.16, 0.), (0.3, 0.)], true),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn small_saucers_only_come_at_high_scores() {
+        let mut rng = Rng::new(1);
+        for _ in 0..100 {
+            assert_eq!(SaucerKind::roll(SMALL_SAUCER_SCORE - 1, &mut rng), SaucerKind::Large);
+            assert_eq!(SaucerKind::roll(ALWAYS_SMALL_SAUCER_SCORE, &mut rng), SaucerKind::Small);
+        }
+    }
+
+    #[test]
+    fn small_saucers_lead_a_moving_target() {
+        let mut rng = Rng::new(1);
+        let mut saucer = Saucer::enter(SaucerKind::Small, 0., &mut rng);
+        saucer.pos = Point { x: 0., y: 0. };
+        let target = Point { x: 0., y: -240. };
+        let target_vel = Velocity { x: 60., y: 0. };
+        let shot = saucer.fire(target, target_vel, SMALL.fire_interval, &mut rng).unwrap();
+        // two seconds out, the target will be 120 pixels to the right
+        let lead = shot.vel.x.atan2(-shot.vel.y).to_degrees();
+        assert!((lead - 26.6).abs() < SMALL.aim_error + 0.1, "{}", lead);
+    }
+}
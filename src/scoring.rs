@@ -0,0 +1,66 @@
+//! how many points breaking up asteroids and downing saucers is worth
+
+use crate::asteroid::Material;
+use crate::events::GameEvent;
+use crate::saucer::SaucerKind;
+
+/// points for breaking a layer of each material off an asteroid with this
+/// many sides: 6 or more is large, 5 medium and 4 small. Smaller asteroids
+/// are harder to hit, so they're worth more, as in the arcade game, and ice
+/// is worth a little more than rock for the extra shot it takes to get
+/// through.
+const LAYER_POINTS: &[(Material, u8, u32)] = &[
+    (Material::Rock, 6, 20),
+    (Material::Rock, 5, 50),
+    (Material::Rock, 4, 100),
+    (Material::Ice, 6, 30),
+    (Material::Ice, 5, 60),
+    (Material::Ice, 4, 120),
+];
+
+/// points for each kind of saucer, as in the arcade game
+const SAUCER_POINTS: u32 = 200;
+const SMALL_SAUCER_POINTS: u32 = 1000;
+
+/// points for each second a wave is cleared under par
+const SPEED_BONUS_PER_SEC: f64 = 10.;
+
+/// points between each bonus life unless the balance file says otherwise,
+/// as in the arcade game
+pub const EXTRA_LIFE_POINTS: u32 = 10_000;
+
+/// how many bonus lives going from one score to another earns, with one
+/// every `every` points, or none if that's 0
+pub fn extra_lives_earned(before: u32, after: u32, every: u32) -> u32 {
+    if every == 0 {
+        return 0;
+    }
+    after / every - before / every
+}
+
+/// the bonus for clearing a wave in `secs` against a par time of `par`,
+/// rounded down to the nearest 10 points
+pub fn speed_bonus(secs: f64, par: f64) -> u32 {
+    let bonus = ((par - secs).max(0.) * SPEED_BONUS_PER_SEC) as u32;
+    bonus / 10 * 10
+}
+
+/// what an event adds to the score
+pub fn points(event: &GameEvent) -> u32 {
+    match event {
+        GameEvent::AsteroidDestroyed { sides, material } => layer_points(*material, *sides),
+        GameEvent::ShellBroken { sides } => layer_points(Material::Ice, *sides),
+        GameEvent::SaucerDestroyed(SaucerKind::Large) => SAUCER_POINTS,
+        GameEvent::SaucerDestroyed(SaucerKind::Small) => SMALL_SAUCER_POINTS,
+        GameEvent::WaveCleared { bonus, .. } => *bonus,
+        _ => 0,
+    }
+}
+
+fn layer_points(material: Material, sides: u8) -> u32 {
+    let sides = sides.clamp(4, 6);
+    LAYER_POINTS
+        .iter()
+        .find(|(row_material, row_sides, _)| *row_material == material && *row_sides == sides)
+        .map_or(0, |(_, _, points)| *points)
+}
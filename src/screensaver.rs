@@ -0,0 +1,96 @@
+//! a calm field of drifting asteroids for menus nobody is looking at
+
+use crate::asteroid::generate_asteroid;
+use crate::input::InputFrame;
+use crate::physics::frame_delta;
+use crate::random;
+use crate::settings::{Density, Difficulty, Edges};
+use crate::world::World;
+use macroquad::prelude::*;
+
+pub const SCREENSAVER_DELAY: f64 = 30.;
+const SCREENSAVER_FADE_TIME: f64 = 2.;
+
+/// a calm field of slowly drifting asteroids, shown when nobody has touched a
+/// menu for a while. They move the same way they do in play, bouncing off
+/// each other, in a world left to run on its own.
+pub struct Screensaver {
+    pub world: World,
+    pub start_time: f64,
+}
+impl Screensaver {
+    pub fn new(now: f64) -> Screensaver {
+        let mut world = World::new(Density::Classic, Difficulty::default(), random::fresh_seed());
+        // nothing but the asteroids: no wave coming in, no saucers, and the
+        // ship is never drawn or hit
+        world.spawn_warnings.clear();
+        world.next_saucer = f64::INFINITY;
+        let center = world.ship.pos;
+        world.asteroids = (0..8)
+            .map(|_| {
+                let mut asteroid = random::effects(|rng| generate_asteroid(center, 0., 1, rng));
+                // drift rather than fly
+                asteroid.vel.x *= 0.3;
+                asteroid.vel.y *= 0.3;
+                asteroid.rot_speed *= 0.3;
+                asteroid
+            })
+            .collect();
+
+        Screensaver {
+            world,
+            start_time: now,
+        }
+    }
+
+    pub fn update(&mut self) {
+        self.world.update(
+            &InputFrame::default(),
+            Edges::Wrap,
+            true,
+            frame_delta(),
+            &mut Vec::new(),
+        );
+    }
+
+    pub fn draw(&self, now: f64) {
+        let elapsed = (now - self.start_time) as f32;
+        let fade = (elapsed / SCREENSAVER_FADE_TIME as f32).min(1.);
+
+        clear_background(LIGHTGRAY);
+        draw_rectangle(
+            0.,
+            0.,
+            screen_width(),
+            screen_height(),
+            Color::new(0.05, 0.05, 0.1, fade),
+        );
+
+        // a slow pan and zoom, kept inside the screen so wrapping never shows
+        let scale = 0.88 + (elapsed * 0.05).sin() * 0.06;
+        let width = screen_width() * scale;
+        let height = screen_height() * scale;
+        let center_x = screen_width() / 2. + (elapsed * 0.03).sin() * (screen_width() - width) / 2.;
+        let center_y = screen_height() / 2. + (elapsed * 0.02).cos() * (screen_height() - height) / 2.;
+        set_camera(&Camera2D::from_display_rect(Rect::new(
+            center_x - width / 2.,
+            center_y - height / 2.,
+            width,
+            height,
+        )));
+
+        for asteroid in self.world.asteroids.iter() {
+            draw_poly_lines(
+                asteroid.pos.x,
+                asteroid.pos.y,
+                asteroid.sides,
+                asteroid.size,
+                asteroid.rotation.degrees(),
+                2.,
+                Color::new(0.8, 0.8, 0.85, fade),
+            );
+        }
+
+        set_default_camera();
+    }
+}
@@ -0,0 +1,161 @@
+//! secret key sequences, typed in on the pause screen
+
+use crate::input::{key_from_name, key_name};
+use crate::storage::Storage;
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+const SECRETS_FILE: &str = "secrets.toml";
+
+/// up, up, down, down, left, right, left, right, B, A
+const KONAMI_CODE: &[KeyCode] = &[
+    KeyCode::Up,
+    KeyCode::Up,
+    KeyCode::Down,
+    KeyCode::Down,
+    KeyCode::Left,
+    KeyCode::Right,
+    KeyCode::Left,
+    KeyCode::Right,
+    KeyCode::B,
+    KeyCode::A,
+];
+const GIMME_CODE: &[KeyCode] = &[KeyCode::G, KeyCode::I, KeyCode::M, KeyCode::M, KeyCode::E];
+const HITBOX_CODE: &[KeyCode] = &[KeyCode::H, KeyCode::I, KeyCode::T, KeyCode::B, KeyCode::O, KeyCode::X];
+
+/// what a secret code does when it's typed
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Secret {
+    /// turns rainbow mode on or off
+    Rainbow,
+    /// a few more ships
+    ExtraLives,
+    /// turns on or off outlines of the shapes that collide
+    Hitboxes,
+}
+impl Secret {
+    /// whether the secret gives the player an edge. A run that used a cheat
+    /// can't make the high score table.
+    pub fn is_cheat(&self) -> bool {
+        !matches!(self, Secret::Rainbow)
+    }
+}
+
+/// the secret codes and the last few keys pressed, to spot them in
+pub struct Secrets {
+    codes: Vec<(Vec<KeyCode>, Secret)>,
+    recent: VecDeque<KeyCode>,
+}
+impl Default for Secrets {
+    fn default() -> Secrets {
+        Secrets::new(vec![
+            (KONAMI_CODE.to_vec(), Secret::Rainbow),
+            (GIMME_CODE.to_vec(), Secret::ExtraLives),
+            (HITBOX_CODE.to_vec(), Secret::Hitboxes),
+        ])
+    }
+}
+impl Secrets {
+    fn new(codes: Vec<(Vec<KeyCode>, Secret)>) -> Secrets {
+        Secrets {
+            codes,
+            recent: VecDeque::new(),
+        }
+    }
+
+    /// the codes in the secrets file, leaving out any with unknown keys. A
+    /// missing file is written out with the defaults so there's something
+    /// to edit. Returns a notice for the player if the file couldn't be
+    /// used.
+    pub fn load(storage: &dyn Storage) -> (Secrets, Option<String>) {
+        /// the secrets file as written, with keys by name
+        #[derive(Default, Serialize, Deserialize)]
+        #[serde(default)]
+        struct Codes {
+            codes: Vec<Code>,
+        }
+        #[derive(Serialize, Deserialize)]
+        struct Code {
+            keys: Vec<String>,
+            secret: Secret,
+        }
+
+        let text = match storage.read(SECRETS_FILE) {
+            Ok(Some(text)) => text,
+            Ok(None) => {
+                let defaults = Secrets::default();
+                let codes = Codes {
+                    codes: defaults
+                        .codes
+                        .iter()
+                        .map(|(keys, secret)| Code {
+                            keys: keys.iter().filter_map(|key| key_name(*key)).collect(),
+                            secret: *secret,
+                        })
+                        .collect(),
+                };
+                // if this fails the defaults still work, and it's tried
+                // again next time
+                if let Ok(text) = toml::to_string(&codes) {
+                    let _ = storage.write(SECRETS_FILE, text.as_bytes());
+                }
+                return (defaults, None);
+            }
+            Err(err) => return (Secrets::default(), Some(format!("{}. Using the default secrets.", err))),
+        };
+        let codes: Codes = match toml::from_str(&text) {
+            Ok(codes) => codes,
+            Err(err) => {
+                let notice = format!(
+                    "{} isn't valid, so the default secrets are in use: {}",
+                    storage.path(SECRETS_FILE).display(),
+                    err.message()
+                );
+                return (Secrets::default(), Some(notice));
+            }
+        };
+
+        let mut unknown = Vec::new();
+        let mut secrets = Vec::new();
+        for code in codes.codes {
+            let keys: Option<Vec<KeyCode>> = code.keys.iter().map(|name| key_from_name(name)).collect();
+            match keys {
+                Some(keys) if !keys.is_empty() => secrets.push((keys, code.secret)),
+                _ => unknown.push(code.keys.join(" ")),
+            }
+        }
+        let notice = (!unknown.is_empty()).then(|| {
+            format!(
+                "Unknown keys in {}, so these codes are left out: {}",
+                storage.path(SECRETS_FILE).display(),
+                unknown.join(", ")
+            )
+        });
+        (Secrets::new(secrets), notice)
+    }
+
+    /// notes the key pressed this frame, if any, and returns the secret
+    /// whose code it finished
+    pub fn update(&mut self) -> Option<Secret> {
+        let key = get_last_key_pressed()?;
+        self.recent.push_back(key);
+        let longest = self.codes.iter().map(|(keys, _)| keys.len()).max().unwrap_or(0);
+        while self.recent.len() > longest {
+            self.recent.pop_front();
+        }
+
+        let (_, secret) = self.codes.iter().find(|(keys, _)| self.ends_with(keys))?;
+        let secret = *secret;
+        // forget the keys so far, so a finished code doesn't count twice
+        self.recent.clear();
+        Some(secret)
+    }
+
+    /// whether the last keys pressed were `sequence`
+    fn ends_with(&self, sequence: &[KeyCode]) -> bool {
+        self.recent.len() >= sequence.len()
+            && self.recent.iter().rev().zip(sequence.iter().rev()).all(|(a, b)| a == b)
+    }
+}
@@ -0,0 +1,222 @@
+//! player preferences and the rules a game is played by
+
+use crate::crash::StartupError;
+use crate::migrate::SETTINGS_MIGRATIONS;
+use crate::ship::STARTING_LIVES;
+use crate::storage::{self, Storage};
+use serde::{Deserialize, Serialize};
+
+const MIN_ASTEROIDS: usize = 4;
+const SETTINGS_FILE: &str = "settings.json";
+
+/// player preferences, kept on disk between runs. Any left out of the file
+/// take their defaults.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub show_damage_numbers: bool,
+    pub show_event_feed: bool,
+    pub show_danger_vignette: bool,
+    pub density: Density,
+    pub difficulty: Difficulty,
+    pub controls: Controls,
+    /// from 0. (silent) to 1.
+    pub music_volume: f32,
+    pub effects_volume: f32,
+    /// whether practice runs show where the asteroids are headed
+    pub show_trajectories: bool,
+    /// whether asteroids bounce off each other rather than passing through
+    pub asteroids_bounce: bool,
+    /// whether to show where the ship will drift, and where thrusting
+    /// would take it instead
+    pub show_flight_path: bool,
+}
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings {
+            show_damage_numbers: false,
+            show_event_feed: true,
+            show_danger_vignette: true,
+            density: Density::Classic,
+            difficulty: Difficulty::Normal,
+            controls: Controls::Keys,
+            music_volume: 0.5,
+            effects_volume: 1.,
+            show_trajectories: true,
+            asteroids_bounce: false,
+            show_flight_path: false,
+        }
+    }
+}
+impl Settings {
+    /// the settings saved last time, or the defaults if there aren't any
+    /// yet. See `load_versioned` for what happens to old or broken files.
+    pub fn load(storage: &dyn Storage) -> Result<(Settings, Option<String>), StartupError> {
+        storage::load_versioned(storage, SETTINGS_FILE, SETTINGS_MIGRATIONS, "the settings")
+    }
+
+    pub fn save(&self, storage: &dyn Storage) -> Result<(), String> {
+        storage::save_versioned(storage, SETTINGS_FILE, self, SETTINGS_MIGRATIONS)
+    }
+}
+
+/// how crowded the asteroid field starts. The count scales with the area of
+/// the world so a big monitor isn't any emptier than a small one.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Density {
+    Sparse,
+    Classic,
+    Dense,
+}
+impl Density {
+    /// asteroids per megapixel of world area. Classic gives the original ten
+    /// asteroids in the default 800x600 window.
+    pub fn per_megapixel(&self) -> f32 {
+        match self {
+            Density::Sparse => 12.,
+            Density::Classic => 21.,
+            Density::Dense => 32.,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Density::Sparse => "Sparse",
+            Density::Classic => "Classic",
+            Density::Dense => "Dense",
+        }
+    }
+
+    pub fn next(&self) -> Density {
+        match self {
+            Density::Sparse => Density::Classic,
+            Density::Classic => Density::Dense,
+            Density::Dense => Density::Sparse,
+        }
+    }
+
+    pub fn previous(&self) -> Density {
+        match self {
+            Density::Sparse => Density::Dense,
+            Density::Classic => Density::Sparse,
+            Density::Dense => Density::Classic,
+        }
+    }
+
+    /// how many asteroids to start with in a world of the given size
+    pub fn asteroid_count(&self, width: f32, height: f32) -> usize {
+        let megapixels = width * height / 1_000_000.;
+        ((megapixels * self.per_megapixel()).round() as usize).max(MIN_ASTEROIDS)
+    }
+}
+
+/// how hard a run is. It's fixed when the run starts, so changing it
+/// part way through only counts from the next one.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+impl Difficulty {
+    /// ships a run starts with, including the one in play
+    pub fn starting_lives(&self) -> u8 {
+        match self {
+            Difficulty::Easy => 5,
+            Difficulty::Normal => STARTING_LIVES,
+            Difficulty::Hard => 2,
+        }
+    }
+
+    /// how fast asteroids move, compared to Normal
+    pub fn asteroid_speed(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.75,
+            Difficulty::Normal => 1.,
+            Difficulty::Hard => 1.3,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+        }
+    }
+
+    pub fn next(&self) -> Difficulty {
+        match self {
+            Difficulty::Easy => Difficulty::Normal,
+            Difficulty::Normal => Difficulty::Hard,
+            Difficulty::Hard => Difficulty::Easy,
+        }
+    }
+
+    pub fn previous(&self) -> Difficulty {
+        match self {
+            Difficulty::Easy => Difficulty::Hard,
+            Difficulty::Normal => Difficulty::Easy,
+            Difficulty::Hard => Difficulty::Normal,
+        }
+    }
+}
+
+/// how the ship is steered. The keyboard and gamepad always work; the
+/// schemes differ in what else does.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Controls {
+    Keys,
+    /// holding the right mouse button swings the ship toward the cursor
+    MouseAim,
+}
+impl Controls {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Controls::Keys => "Keyboard",
+            Controls::MouseAim => "Keyboard, mouse to aim",
+        }
+    }
+
+    pub fn next(&self) -> Controls {
+        match self {
+            Controls::Keys => Controls::MouseAim,
+            Controls::MouseAim => Controls::Keys,
+        }
+    }
+}
+
+/// rules that change how a game plays, picked before it starts
+#[derive(Debug, Default, Serialize)]
+pub struct Rules {
+    pub edges: Edges,
+    /// a run for trying things out: ships aren't used up and the score
+    /// doesn't count toward the high scores
+    pub practice: bool,
+}
+
+/// what happens when something reaches the edge of the screen
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize)]
+pub enum Edges {
+    /// leave one side and come back on the other, like the arcade game
+    #[default]
+    Wrap,
+    /// solid arena walls that ships and asteroids bounce off
+    Walls,
+}
+impl Edges {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Edges::Wrap => "Wrap around",
+            Edges::Walls => "Arena walls",
+        }
+    }
+
+    pub fn next(&self) -> Edges {
+        match self {
+            Edges::Wrap => Edges::Walls,
+            Edges::Walls => Edges::Wrap,
+        }
+    }
+}
@@ -0,0 +1,150 @@
+//! share codes, summing up how a run was set up so friends can try the same one
+
+use crate::settings::{Density, Difficulty, Edges, Rules, Settings};
+use macroquad::window::get_internal_gl;
+use std::fmt;
+
+/// the seed a run's world was rolled from and the options it was played
+/// with. Written out it looks like `CW-1A2B3C4D`: the density, the screen
+/// edges, a P for a practice run, an E or H for Easy or Hard, then the seed
+/// in hex.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShareCode {
+    pub seed: u64,
+    pub density: Density,
+    pub difficulty: Difficulty,
+    pub edges: Edges,
+    pub practice: bool,
+}
+impl ShareCode {
+    pub fn new(seed: u64, settings: &Settings, rules: &Rules) -> ShareCode {
+        ShareCode {
+            seed,
+            density: settings.density,
+            difficulty: settings.difficulty,
+            edges: rules.edges,
+            practice: rules.practice,
+        }
+    }
+
+    /// reads a code as written by `to_string`, in either case and with any
+    /// spaces around it. Returns None if it isn't one.
+    pub fn parse(code: &str) -> Option<ShareCode> {
+        let code = code.trim().to_ascii_uppercase();
+        let (mode, seed) = code.split_once('-')?;
+        let mut letters = mode.chars();
+        let density = match letters.next()? {
+            'S' => Density::Sparse,
+            'C' => Density::Classic,
+            'D' => Density::Dense,
+            _ => return None,
+        };
+        let edges = match letters.next()? {
+            'W' => Edges::Wrap,
+            'A' => Edges::Walls,
+            _ => return None,
+        };
+        let mut letters = letters.peekable();
+        let practice = letters.next_if_eq(&'P').is_some();
+        let difficulty = match letters.next() {
+            Some('E') => Difficulty::Easy,
+            Some('H') => Difficulty::Hard,
+            None => Difficulty::Normal,
+            Some(_) => return None,
+        };
+        if letters.next().is_some() {
+            return None;
+        }
+        Some(ShareCode {
+            seed: u64::from_str_radix(seed, 16).ok()?,
+            density,
+            difficulty,
+            edges,
+            practice,
+        })
+    }
+}
+impl fmt::Display for ShareCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let density = match self.density {
+            Density::Sparse => 'S',
+            Density::Classic => 'C',
+            Density::Dense => 'D',
+        };
+        let edges = match self.edges {
+            Edges::Wrap => 'W',
+            Edges::Walls => 'A',
+        };
+        let practice = if self.practice { "P" } else { "" };
+        let difficulty = match self.difficulty {
+            Difficulty::Easy => "E",
+            Difficulty::Normal => "",
+            Difficulty::Hard => "H",
+        };
+        write!(f, "{}{}{}{}-{:X}", density, edges, practice, difficulty, self.seed)
+    }
+}
+
+/// puts `text` on the system clipboard. Some desktops (Wayland) don't
+/// support this and nothing happens.
+pub fn copy_to_clipboard(text: &str) {
+    // SAFETY: the internal context is only unsafe to take while macroquad
+    // has its own hold on it, in the middle of drawing. Menus are updated
+    // from the main loop between frames, where nothing else holds it, and
+    // the reference is dropped before returning.
+    let gl = unsafe { get_internal_gl() };
+    gl.quad_context.clipboard_set(text);
+}
+
+/// what's on the system clipboard, if it's text
+pub fn paste_from_clipboard() -> Option<String> {
+    // SAFETY: as in `copy_to_clipboard`
+    let gl = unsafe { get_internal_gl() };
+    gl.quad_context.clipboard_get()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_read_back_the_same() {
+        for density in [Density::Sparse, Density::Classic, Density::Dense] {
+            for edges in [Edges::Wrap, Edges::Walls] {
+                for practice in [false, true] {
+                    for difficulty in [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard] {
+                        let code = ShareCode {
+                            seed: 0xDEAD_BEEF,
+                            density,
+                            difficulty,
+                            edges,
+                            practice,
+                        };
+                        assert_eq!(ShareCode::parse(&code.to_string()), Some(code));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn codes_are_read_loosely() {
+        let code = ShareCode::parse("  dap-00ff \n").unwrap();
+        assert_eq!(code.seed, 0xFF);
+        assert_eq!(code.density, Density::Dense);
+        assert_eq!(code.edges, Edges::Walls);
+        assert!(code.practice);
+        assert_eq!(code.difficulty, Difficulty::Normal);
+        assert_eq!(ShareCode::parse("cwh-1").unwrap().difficulty, Difficulty::Hard);
+    }
+
+    #[test]
+    fn other_text_is_not_a_code() {
+        let not_codes = [
+            "", "CW", "CW-", "XW-1", "CX-1", "CWQ-1", "CWPP-1", "CWHP-1", "CWEH-1", "CW-XYZ", "CW-1-2",
+        ];
+        for text in not_codes {
+            assert_eq!(ShareCode::parse(text), None, "{:?}", text);
+        }
+    }
+}
@@ -0,0 +1,418 @@
+//! the player's ship
+
+use crate::angle::Angle;
+use crate::effects::DamageFlash;
+use crate::input::InputFrame;
+use crate::physics::{Point, TimeScale, Velocity};
+use crate::weapon::Weapon;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+pub const SHIP_HEIGHT: f32 = 25.;
+pub const SHIP_BASE: f32 = 22.;
+pub const CRIT_CHANCE: f32 = 0.08;
+pub const STARTING_LIVES: u8 = 3;
+/// seconds a freshly respawned ship can't be hit
+const RESPAWN_INVULNERABILITY: f64 = 3.;
+/// seconds the ship can't be hit after its shield takes a hit, to get clear
+const SHIELD_GRACE: f64 = 1.;
+/// a full energy meter
+pub const MAX_ENERGY: f32 = 1.;
+/// energy a hit drains from the meter
+const HIT_ENERGY: f32 = 0.4;
+/// energy the meter regains each second
+const ENERGY_RECHARGE: f32 = 0.05;
+/// how hot the laser can get before it shuts off to cool down
+pub const MAX_HEAT: f32 = 1.;
+/// heat the laser builds up each second it fires
+const LASER_HEATING: f32 = 0.5;
+/// heat the laser loses each second it's off
+const LASER_COOLING: f32 = 0.35;
+/// damage the laser deals each second it burns an asteroid
+const LASER_DAMAGE_RATE: f32 = 10.;
+/// smart bombs the ship starts each life with
+pub const BOMBS_PER_LIFE: u8 = 2;
+/// the most smart bombs the ship can carry
+const MAX_BOMBS: u8 = 5;
+/// seconds between smart bombs, so holding the key doesn't set them all off
+const BOMB_COOLDOWN: f64 = 1.;
+/// seconds after a hyperspace jump before the drive can jump again
+const HYPERSPACE_COOLDOWN: f64 = 3.;
+/// pixels per second gained each second of thrust
+const SHIP_THRUST: f32 = 600.;
+/// fraction of the ship's speed lost each second while coasting
+const SHIP_DRAG: f32 = 0.6;
+/// below this speed, in pixels per second, the ship stops slowing down
+const SHIP_DRIFT_SPEED: f32 = 6.;
+/// degrees per second
+const SHIP_TURN_SPEED: f32 = 180.;
+/// how quickly the ship swings toward the cursor when aiming with the mouse.
+/// Higher is snappier.
+const AIM_RATE: f32 = 12.;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Ship {
+    pub pos: Point,
+    pub vel: Velocity,
+    pub rotation: Angle,
+    /// chance for each bullet to land a critical hit. Upgrades can raise it.
+    pub crit_chance: f32,
+    #[serde(default)]
+    pub time_scale: TimeScale,
+    /// asteroids pass straight through the ship until this time
+    #[serde(default)]
+    pub invulnerable_until: f64,
+    /// whether a shield is up to take the next hit
+    #[serde(default)]
+    pub shield: bool,
+    /// shots come twice as fast until this time
+    #[serde(default)]
+    pub rapid_fire_until: f64,
+    /// the spread gun can be used until this time
+    #[serde(default)]
+    pub spread_until: f64,
+    /// the gun that fires
+    #[serde(default)]
+    pub weapon: Weapon,
+    /// shots left for each weapon that uses ammo
+    #[serde(default)]
+    pub ammo: BTreeMap<Weapon, u32>,
+    /// how hot the laser is, up to `MAX_HEAT`
+    #[serde(default)]
+    pub heat: f32,
+    /// the laser reached `MAX_HEAT` and won't fire until it has cooled
+    /// right down
+    #[serde(default)]
+    pub overheated: bool,
+    /// whether the laser is firing this step
+    #[serde(skip)]
+    pub firing_laser: bool,
+    /// how far the laser beam reaches, to the first asteroid in its way
+    #[serde(skip)]
+    pub laser_reach: f32,
+    /// damage the laser has built up burning asteroids that doesn't come
+    /// to a whole hit yet
+    #[serde(default)]
+    pub laser_burn: f32,
+    /// the shield meter, from empty up to `MAX_ENERGY`. Hits drain it, and
+    /// only a hit with too little left to take it destroys the ship.
+    #[serde(default = "default_energy")]
+    pub energy: f32,
+    /// flashes when a hit is absorbed
+    #[serde(default)]
+    pub flash: DamageFlash,
+    /// smart bombs left
+    #[serde(default = "default_bombs")]
+    pub bombs: u8,
+    /// another smart bomb can't go off until this time
+    #[serde(default)]
+    pub bomb_ready: f64,
+    /// the hyperspace drive can't jump again until this time
+    #[serde(default)]
+    pub hyperspace_ready: f64,
+    /// whether the engine fired on the last step, for drawing the exhaust
+    #[serde(skip)]
+    pub thrusting: bool,
+    /// where the ship was at the start of the last simulation step
+    #[serde(skip)]
+    pub last_pos: Point,
+    #[serde(skip)]
+    pub last_rotation: Angle,
+}
+impl Default for Ship {
+    fn default() -> Ship {
+        Ship {
+            pos: Point::default(),
+            vel: Velocity::default(),
+            rotation: Angle::default(),
+            crit_chance: CRIT_CHANCE,
+            time_scale: TimeScale::default(),
+            invulnerable_until: 0.,
+            shield: false,
+            rapid_fire_until: 0.,
+            spread_until: 0.,
+            weapon: Weapon::default(),
+            ammo: BTreeMap::new(),
+            heat: 0.,
+            overheated: false,
+            firing_laser: false,
+            laser_reach: 0.,
+            laser_burn: 0.,
+            energy: MAX_ENERGY,
+            flash: DamageFlash::default(),
+            bombs: BOMBS_PER_LIFE,
+            bomb_ready: 0.,
+            hyperspace_ready: 0.,
+            thrusting: false,
+            last_pos: Point::default(),
+            last_rotation: Angle::default(),
+        }
+    }
+}
+impl Ship {
+    /// thrusts and turns the ship by the controls held over the last `delta`
+    /// seconds
+    pub fn steer(&mut self, input: &InputFrame, delta: f32) {
+        let delta = self.time_scale.apply(delta);
+        self.thrusting = input.thrust;
+        if input.thrust {
+            self.vel.add_at_angle(SHIP_THRUST * delta, self.rotation.degrees())
+        } else {
+            // decelerate over time
+            let drag = SHIP_DRAG * delta;
+            if self.vel.x > SHIP_DRIFT_SPEED {
+                self.vel.x -= drag * self.vel.x.abs();
+            } else if self.vel.x < -SHIP_DRIFT_SPEED {
+                self.vel.x += drag * self.vel.x.abs();
+            }
+            if self.vel.y > SHIP_DRIFT_SPEED {
+                self.vel.y -= drag * self.vel.y.abs();
+            } else if self.vel.y < -SHIP_DRIFT_SPEED {
+                self.vel.y += drag * self.vel.y.abs();
+            }
+        }
+
+        if input.right {
+            self.rotation.rotate(SHIP_TURN_SPEED * delta);
+        } else if input.left {
+            self.rotation.rotate(-SHIP_TURN_SPEED * delta);
+        } else if let Some(target) = input.aim {
+            // swing the nose around toward the cursor
+            let aim = Angle::towards(self.pos, target);
+            self.rotation = self.rotation.lerp(aim, 1. - (-AIM_RATE * delta).exp());
+        }
+    }
+
+    /// puts the ship back at `pos`, at rest and facing up, with a few
+    /// seconds before anything can hit it
+    pub fn respawn(&mut self, pos: Point, now: f64) {
+        self.pos = pos;
+        self.last_pos = pos;
+        self.vel = Velocity::default();
+        self.rotation = Angle::default();
+        self.last_rotation = self.rotation;
+        self.invulnerable_until = now + RESPAWN_INVULNERABILITY;
+        self.energy = MAX_ENERGY;
+        self.bombs = self.bombs.max(BOMBS_PER_LIFE);
+    }
+
+    /// tops the energy meter back up over `delta` seconds
+    pub fn recharge(&mut self, delta: f32) {
+        let delta = self.time_scale.apply(delta);
+        self.energy = (self.energy + ENERGY_RECHARGE * delta).min(MAX_ENERGY);
+    }
+
+    pub fn can_bomb(&self, now: f64) -> bool {
+        self.bombs > 0 && now >= self.bomb_ready
+    }
+
+    /// uses up a smart bomb
+    pub fn drop_bomb(&mut self, now: f64) {
+        self.bombs -= 1;
+        self.bomb_ready = now + BOMB_COOLDOWN;
+    }
+
+    /// adds a smart bomb, up to the most the ship can carry
+    pub fn add_bomb(&mut self) {
+        self.bombs = (self.bombs + 1).min(MAX_BOMBS);
+    }
+
+    pub fn can_jump(&self, now: f64) -> bool {
+        now >= self.hyperspace_ready
+    }
+
+    /// vanishes and reappears at `pos`, at rest, and starts the hyperspace
+    /// drive's cooldown
+    pub fn jump(&mut self, pos: Point, now: f64) {
+        self.pos = pos;
+        self.last_pos = pos;
+        self.vel = Velocity::default();
+        self.hyperspace_ready = now + HYPERSPACE_COOLDOWN;
+    }
+
+    /// the corners of the ship's triangle: the nose, then the left and
+    /// right of its base
+    pub fn outline(&self) -> [Point; 3] {
+        let (sin, cos) = self.rotation.radians().sin_cos();
+        let corner = |across: f32, along: f32| Point {
+            x: self.pos.x + cos * across + sin * along,
+            y: self.pos.y + sin * across - cos * along,
+        };
+        [
+            corner(0., SHIP_HEIGHT / 2.),
+            corner(-SHIP_BASE / 2., -SHIP_HEIGHT / 2.),
+            corner(SHIP_BASE / 2., -SHIP_HEIGHT / 2.),
+        ]
+    }
+
+    /// uses up the shield power-up, if there is one, or else drains the
+    /// energy meter to take a hit, leaving the ship a moment to get clear.
+    /// Returns whether the hit was absorbed.
+    pub fn absorb_hit(&mut self, now: f64) -> bool {
+        if self.shield {
+            self.shield = false;
+        } else if self.has_energy_for_hit() {
+            self.energy -= HIT_ENERGY;
+        } else {
+            return false;
+        }
+        self.invulnerable_until = now + SHIELD_GRACE;
+        true
+    }
+
+    /// whether the energy meter has enough left to take a hit
+    pub fn has_energy_for_hit(&self) -> bool {
+        self.energy >= HIT_ENERGY
+    }
+
+    pub fn is_invulnerable(&self, now: f64) -> bool {
+        now < self.invulnerable_until
+    }
+
+    /// whether enough time has passed since `last_shot` to fire again
+    pub fn can_fire(&self, last_shot: f64, now: f64) -> bool {
+        let cooldown = if self.rapid_fire_left(now) > 0. {
+            self.weapon.cooldown() / 2.
+        } else {
+            self.weapon.cooldown()
+        };
+        (now - last_shot) * self.time_scale.0 as f64 > cooldown
+    }
+
+    /// seconds of rapid fire left, or 0. if there isn't any
+    pub fn rapid_fire_left(&self, now: f64) -> f64 {
+        (self.rapid_fire_until - now).max(0.)
+    }
+
+    /// seconds of the spread gun left, or 0. if there isn't any
+    pub fn spread_left(&self, now: f64) -> f64 {
+        (self.spread_until - now).max(0.)
+    }
+
+    /// shots left for `weapon`, or `None` if it never runs out
+    pub fn ammo(&self, weapon: Weapon) -> Option<u32> {
+        weapon
+            .uses_ammo()
+            .then(|| self.ammo.get(&weapon).copied().unwrap_or(0))
+    }
+
+    /// whether `weapon` has any shots or time left
+    pub fn can_use(&self, weapon: Weapon, now: f64) -> bool {
+        match weapon {
+            Weapon::Spread => self.spread_left(now) > 0.,
+            _ => self.ammo(weapon) != Some(0),
+        }
+    }
+
+    /// adds `shots` to `weapon`'s ammo and switches to it
+    pub fn pick_up_ammo(&mut self, weapon: Weapon, shots: u32) {
+        *self.ammo.entry(weapon).or_insert(0) += shots;
+        self.weapon = weapon;
+    }
+
+    /// uses up a shot of the current weapon's ammo, going back to the
+    /// blaster once it's gone
+    pub fn spend_ammo(&mut self, now: f64) {
+        if let Some(shots) = self.ammo.get_mut(&self.weapon) {
+            *shots = shots.saturating_sub(1);
+        }
+        self.put_away_spent_weapon(now);
+    }
+
+    /// goes back to the blaster if the current weapon has run out of shots
+    /// or time
+    pub fn put_away_spent_weapon(&mut self, now: f64) {
+        if !self.can_use(self.weapon, now) {
+            self.weapon = Weapon::Blaster;
+        }
+    }
+
+    /// fires the laser while `trigger` is held and it isn't overheated,
+    /// heating it up, and otherwise lets it cool over `delta` seconds
+    pub fn update_laser(&mut self, trigger: bool, delta: f32) {
+        let delta = self.time_scale.apply(delta);
+        self.firing_laser = trigger && !self.overheated;
+        if self.firing_laser {
+            self.heat = (self.heat + LASER_HEATING * delta).min(MAX_HEAT);
+            self.overheated = self.heat >= MAX_HEAT;
+        } else {
+            self.heat = (self.heat - LASER_COOLING * delta).max(0.);
+            self.overheated &= self.heat > 0.;
+            self.laser_burn = 0.;
+        }
+    }
+
+    /// burns whatever the laser is touching for `delta` seconds, and
+    /// returns how much whole damage that has come to
+    pub fn burn(&mut self, delta: f32) -> u8 {
+        self.laser_burn += LASER_DAMAGE_RATE * self.time_scale.apply(delta);
+        let damage = self.laser_burn.floor();
+        self.laser_burn -= damage;
+        damage as u8
+    }
+
+    /// switches to `weapon` if it has shots or time left
+    pub fn select_weapon(&mut self, weapon: Weapon, now: f64) {
+        if self.can_use(weapon, now) {
+            self.weapon = weapon;
+        }
+    }
+
+    /// switches to the next weapon that has shots or time left
+    pub fn cycle_weapon(&mut self, now: f64) {
+        let current = Weapon::ALL.iter().position(|weapon| *weapon == self.weapon).unwrap_or(0);
+        for step in 1..=Weapon::ALL.len() {
+            let weapon = Weapon::ALL[(current + step) % Weapon::ALL.len()];
+            if self.can_use(weapon, now) {
+                self.weapon = weapon;
+                return;
+            }
+        }
+    }
+
+    pub fn advance(&mut self, delta: f32) {
+        let delta = self.time_scale.apply(delta);
+        self.pos.x += self.vel.x * delta;
+        self.pos.y += self.vel.y * delta;
+    }
+}
+
+/// where something was relative to the way the ship was facing
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Direction {
+    Ahead,
+    Side,
+    Behind,
+}
+impl Direction {
+    pub fn of(target: Point, ship: &Ship) -> Direction {
+        let off_nose = ship
+            .rotation
+            .shortest_difference(Angle::towards(ship.pos, target))
+            .abs();
+
+        if off_nose < 60. {
+            Direction::Ahead
+        } else if off_nose > 120. {
+            Direction::Behind
+        } else {
+            Direction::Side
+        }
+    }
+
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Direction::Ahead => "head on",
+            Direction::Side => "from the side",
+            Direction::Behind => "from behind",
+        }
+    }
+}
+
+fn default_energy() -> f32 {
+    MAX_ENERGY
+}
+
+fn default_bombs() -> u8 {
+    BOMBS_PER_LIFE
+}
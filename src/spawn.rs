@@ -0,0 +1,151 @@
+//! the formations a wave's asteroids arrive in
+
+use crate::asteroid::{asteroid_at, generate_asteroid, starting_size, Asteroid};
+use crate::physics::{field_size, wrap_around, Point, Velocity};
+use crate::random::{Rng, WeightedTable};
+use crate::ship::SHIP_HEIGHT;
+
+/// how far a ring's asteroids start from the ship, as a fraction of the
+/// screen's shorter side
+const RING_RADIUS: f32 = 0.4;
+/// how far a cluster's asteroids spread out around its middle, in
+/// asteroid sizes
+const CLUSTER_SPREAD: f32 = 1.5;
+const MAX_CLUSTERS: usize = 3;
+
+/// how a wave's asteroids are laid out when they come in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SpawnPattern {
+    /// anywhere, clear of the ship
+    Scattered,
+    /// evenly around the ship, closing in on it
+    Ring,
+    /// a line along the far edge from the ship, sweeping across together
+    Wall,
+    /// a few tight groups, each drifting the same way
+    Clusters,
+}
+impl SpawnPattern {
+    /// the pattern for `wave`. The first wave is always scattered so a run
+    /// starts the same way; after that the formations turn up now and then.
+    pub fn for_wave(wave: u32, rng: &mut Rng) -> SpawnPattern {
+        if wave <= 1 {
+            return SpawnPattern::Scattered;
+        }
+        let table = WeightedTable::new(vec![
+            (3., SpawnPattern::Scattered),
+            (1., SpawnPattern::Ring),
+            (1., SpawnPattern::Wall),
+            (1., SpawnPattern::Clusters),
+        ]);
+        *table.pick(rng)
+    }
+
+    /// `count` asteroids for `wave` laid out in this pattern, with the ship
+    /// at `ship_pos`
+    pub fn generate(&self, count: usize, ship_pos: Point, wave: u32, rng: &mut Rng) -> Vec<Asteroid> {
+        let mut asteroids = match self {
+            SpawnPattern::Scattered => (0..count)
+                .map(|_| generate_asteroid(ship_pos, SHIP_HEIGHT * 3., wave, rng))
+                .collect(),
+            SpawnPattern::Ring => ring(count, ship_pos, wave, rng),
+            SpawnPattern::Wall => wall(count, ship_pos, wave, rng),
+            SpawnPattern::Clusters => clusters(count, ship_pos, wave, rng),
+        };
+        for asteroid in asteroids.iter_mut() {
+            wrap_around(&mut asteroid.pos);
+            asteroid.last_pos = asteroid.pos;
+        }
+        asteroids
+    }
+}
+
+fn ring(count: usize, ship_pos: Point, wave: u32, rng: &mut Rng) -> Vec<Asteroid> {
+    let (width, height) = field_size();
+    let radius = width.min(height) * RING_RADIUS;
+    let start = rng.range(0., 360.);
+    (0..count)
+        .map(|index| {
+            let radians = (start + index as f32 * 360. / count as f32).to_radians();
+            let (sin, cos) = radians.sin_cos();
+            let mut asteroid = asteroid_at(
+                Point {
+                    x: ship_pos.x + sin * radius,
+                    y: ship_pos.y - cos * radius,
+                },
+                wave,
+                rng,
+            );
+            let speed = rng.range(15., 35.);
+            asteroid.vel = Velocity {
+                x: -sin * speed,
+                y: cos * speed,
+            };
+            asteroid
+        })
+        .collect()
+}
+
+fn wall(count: usize, ship_pos: Point, wave: u32, rng: &mut Rng) -> Vec<Asteroid> {
+    let (width, height) = field_size();
+    let margin = starting_size();
+    let speed = rng.range(25., 45.);
+    // the wall lines up along the edge furthest from the ship, across the
+    // screen's longer side, and sweeps toward the other edge
+    let horizontal = width >= height;
+    let far_side = if horizontal {
+        ship_pos.x < width / 2.
+    } else {
+        ship_pos.y < height / 2.
+    };
+    let sweep = if far_side { -speed } else { speed };
+    (0..count)
+        .map(|index| {
+            let along = (index as f32 + 0.5) / count as f32;
+            let pos = match (horizontal, far_side) {
+                (true, true) => Point { x: width - margin, y: along * height },
+                (true, false) => Point { x: margin, y: along * height },
+                (false, true) => Point { x: along * width, y: height - margin },
+                (false, false) => Point { x: along * width, y: margin },
+            };
+            let mut asteroid = asteroid_at(pos, wave, rng);
+            asteroid.vel = if horizontal {
+                Velocity { x: sweep, y: 0. }
+            } else {
+                Velocity { x: 0., y: sweep }
+            };
+            asteroid
+        })
+        .collect()
+}
+
+fn clusters(count: usize, ship_pos: Point, wave: u32, rng: &mut Rng) -> Vec<Asteroid> {
+    let groups = count.clamp(1, MAX_CLUSTERS);
+    let spread = starting_size() * CLUSTER_SPREAD;
+    let clearance = SHIP_HEIGHT * 3. + starting_size() + spread * 2.;
+    let mut asteroids = Vec::new();
+    for group in 0..groups {
+        let center = rng.point_clear_of(&[(ship_pos, clearance)]);
+        let vel = rng.velocity(10., 50.);
+        // share the asteroids out, with any left over going to the first
+        // groups
+        let members = count / groups + usize::from(group < count % groups);
+        for _ in 0..members {
+            let offset = Point {
+                x: rng.gaussian(0., spread),
+                y: rng.gaussian(0., spread),
+            };
+            let mut asteroid = asteroid_at(
+                Point {
+                    x: center.x + offset.x,
+                    y: center.y + offset.y,
+                },
+                wave,
+                rng,
+            );
+            asteroid.vel = vel;
+            asteroids.push(asteroid);
+        }
+    }
+    asteroids
+}
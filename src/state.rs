@@ -0,0 +1,124 @@
+//! which screen the game is on, and how each one updates and draws
+
+use crate::game::Game;
+use crate::menu::{self, MenuChoice, Menus};
+use crate::physics::frame_delta;
+use macroquad::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GameState {
+    /// the title screen shown at startup
+    Menu,
+    Playing,
+    /// play stopped, with the game's clock frozen until it carries on
+    Paused,
+    /// the last ship was destroyed
+    GameOver,
+    /// the run made the high score table, and is waiting for the player's
+    /// initials
+    NameEntry,
+    /// the settings screen, over the title screen or over a paused game
+    Settings { from_pause: bool },
+    /// the player chose to quit, so the main loop stops
+    Quit,
+}
+impl GameState {
+    /// whether this is one of the menus, which go idle into the screensaver
+    pub fn is_menu(&self) -> bool {
+        !matches!(self, GameState::Playing)
+    }
+
+    /// whether nothing on screen moves, so frames can come slowly
+    pub fn is_still(&self) -> bool {
+        !matches!(self, GameState::Playing | GameState::Menu)
+    }
+
+    /// runs a frame of this state and returns the state for the next one
+    pub fn update(self, game: &mut Game, menus: &mut Menus) -> GameState {
+        if game.audio.handle_volume_keys(&mut game.settings) {
+            if let Err(err) = game.save_settings() {
+                menus.notify(err);
+            }
+        }
+        match self {
+            GameState::Menu | GameState::GameOver => {
+                if self == GameState::Menu {
+                    game.drift_background(frame_delta());
+                }
+                let after_run = self != GameState::Menu;
+                match menus.update(game, after_run) {
+                    MenuChoice::Play => {
+                        game.restart();
+                        GameState::Playing
+                    }
+                    MenuChoice::PlaySeeded(seed) => {
+                        game.restart_from(seed);
+                        GameState::Playing
+                    }
+                    MenuChoice::Settings => GameState::Settings { from_pause: false },
+                    MenuChoice::Quit => GameState::Quit,
+                    MenuChoice::Stay => self,
+                }
+            }
+            GameState::Playing => {
+                if is_key_pressed(KeyCode::P) || is_key_pressed(KeyCode::Escape) {
+                    return GameState::Paused;
+                }
+                match game.update() {
+                    GameState::GameOver if menus.made_high_score(game) => GameState::NameEntry,
+                    state => state,
+                }
+            }
+            GameState::NameEntry => {
+                if menus.enter_initials(game) {
+                    GameState::GameOver
+                } else {
+                    self
+                }
+            }
+            GameState::Paused => {
+                game.watch_for_secrets();
+                if is_key_pressed(KeyCode::P) || is_key_pressed(KeyCode::Escape) {
+                    GameState::Playing
+                } else if is_key_pressed(KeyCode::S) {
+                    GameState::Settings { from_pause: true }
+                } else {
+                    self
+                }
+            }
+            GameState::Settings { from_pause } => match menus.update_settings(game) {
+                true if from_pause => GameState::Paused,
+                true => GameState::Menu,
+                false => self,
+            },
+            GameState::Quit => self,
+        }
+    }
+
+    pub fn draw(&self, game: &Game, menus: &Menus) {
+        match self {
+            GameState::Menu => menus.draw_title(game),
+            GameState::Playing => game.draw(),
+            GameState::Paused => {
+                // the game stays frozen behind the pause screen
+                game.draw();
+                menu::draw_paused();
+            }
+            GameState::GameOver => menus.draw_game_over(game),
+            GameState::NameEntry => {
+                game.draw();
+                menus.draw_name_entry(game);
+            }
+            GameState::Settings { from_pause } => {
+                if *from_pause {
+                    game.draw();
+                } else {
+                    menus.draw_title(game);
+                }
+                menus.draw_settings(game);
+            }
+            GameState::Quit => {}
+        }
+        game.audio.draw_volume();
+    }
+}
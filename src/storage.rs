@@ -0,0 +1,236 @@
+//! where the game keeps its files. Everything it reads or writes goes
+//! through a `Storage`, so where files end up is decided in one place.
+//! Only files on disk are supported for now. A browser build would need a
+//! `Storage` over localStorage, which doesn't exist yet.
+
+use crate::crash::StartupError;
+use crate::migrate::{self, Migration};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// a place to keep the game's files, each known by a short name like
+/// `high_scores.json`
+pub trait Storage {
+    /// the contents of a file, or `None` if there isn't one yet
+    fn read(&self, name: &str) -> Result<Option<String>, String>;
+
+    fn write(&self, name: &str, contents: &[u8]) -> Result<(), String>;
+
+    /// renames a file that can't be read out of the way, so the game can
+    /// carry on without it and nothing the player had is lost. The backup
+    /// is stamped with the time so an earlier one is never overwritten.
+    /// Returns where it went.
+    fn set_aside(&self, name: &str) -> Result<PathBuf, String>;
+
+    /// where a file is kept, for telling the player
+    fn path(&self, name: &str) -> PathBuf;
+}
+
+/// reads a versioned JSON file, upgrading it on disk if it's from an older
+/// version of the game, or gives the default if there isn't one yet. A file
+/// that can't be read, because it isn't valid or is from a newer version, is
+/// backed up and replaced with the default, along with a notice for the
+/// player. `what` names the file's contents in messages, like "the high
+/// scores".
+pub fn load_versioned<T: Default + Serialize + DeserializeOwned>(
+    storage: &dyn Storage,
+    name: &str,
+    migrations: &[Migration],
+    what: &str,
+) -> Result<(T, Option<String>), StartupError> {
+    let startup_error = |message| StartupError {
+        message,
+        path: Some(storage.path(name)),
+    };
+    let json = match storage.read(name).map_err(startup_error)? {
+        Some(json) => json,
+        None => return Ok((T::default(), None)),
+    };
+    let unreadable = format!("Couldn't read {}", what);
+    let problem = match serde_json::from_str(&json) {
+        Ok(mut file) => match migrate::upgrade(&mut file, migrations) {
+            Ok(upgraded) => match serde_json::from_value::<T>(file) {
+                Ok(value) => {
+                    if upgraded {
+                        // if this fails the file is still good, and is
+                        // upgraded again next time
+                        let _ = save_versioned(storage, name, &value, migrations);
+                    }
+                    return Ok((value, None));
+                }
+                Err(_) => unreadable,
+            },
+            Err(_) => format!("{} were saved by a newer version of the game", what),
+        },
+        Err(_) => unreadable,
+    };
+
+    // keep the file for whoever wants to dig what they had out of it, or
+    // for going back to the newer version
+    let backup = storage.set_aside(name).map_err(startup_error)?;
+    let notice = format!(
+        "{}, so they were reset. The old file is at {}",
+        capitalize(&problem),
+        backup.display()
+    );
+    Ok((T::default(), Some(notice)))
+}
+
+/// `text` with its first letter in upper case, to start a sentence
+fn capitalize(text: &str) -> String {
+    let mut letters = text.chars();
+    letters
+        .next()
+        .map_or_else(String::new, |first| first.to_uppercase().chain(letters).collect())
+}
+
+/// writes a JSON file marked with the latest version from `migrations`
+pub fn save_versioned<T: Serialize>(
+    storage: &dyn Storage,
+    name: &str,
+    value: &T,
+    migrations: &[Migration],
+) -> Result<(), String> {
+    let mut file = serde_json::to_value(value).map_err(|err| err.to_string())?;
+    migrate::stamp(&mut file, migrations);
+    let json = serde_json::to_string_pretty(&file).map_err(|err| err.to_string())?;
+    storage.write(name, json.as_bytes())
+}
+
+/// files kept in a directory on disk
+pub struct DirStorage {
+    dir: PathBuf,
+}
+impl DirStorage {
+    /// the directory passed with `--data-dir`, for portable installs, or
+    /// else the platform's usual place for app data: the XDG data directory
+    /// on Linux, AppData on Windows and Application Support on macOS
+    pub fn from_args() -> DirStorage {
+        let mut args = std::env::args().skip(1);
+        let mut dir = None;
+        while let Some(arg) = args.next() {
+            if arg == "--data-dir" {
+                dir = args.next().map(PathBuf::from);
+            } else if let Some(path) = arg.strip_prefix("--data-dir=") {
+                dir = Some(PathBuf::from(path));
+            }
+        }
+
+        DirStorage {
+            // with nowhere better, files go next to the game
+            dir: dir
+                .or_else(|| dirs::data_dir().map(|data| data.join("asteroids")))
+                .unwrap_or_default(),
+        }
+    }
+}
+impl Storage for DirStorage {
+    fn read(&self, name: &str) -> Result<Option<String>, String> {
+        let path = self.path(name);
+        match std::fs::read_to_string(&path) {
+            Ok(contents) => Ok(Some(contents)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(format!("Couldn't read {}: {}", path.display(), err)),
+        }
+    }
+
+    fn write(&self, name: &str, contents: &[u8]) -> Result<(), String> {
+        std::fs::create_dir_all(&self.dir)
+            .map_err(|err| format!("Couldn't create {}: {}", self.dir.display(), err))?;
+        let path = self.path(name);
+        std::fs::write(&path, contents).map_err(|err| format!("Couldn't write {}: {}", path.display(), err))
+    }
+
+    fn set_aside(&self, name: &str) -> Result<PathBuf, String> {
+        let seconds = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs());
+        let path = self.path(name);
+        let backup = self.path(&format!("{}.{}.bak", name, seconds));
+
+        std::fs::rename(&path, &backup)
+            .map_err(|err| format!("Couldn't back up {}: {}", path.display(), err))?;
+        Ok(backup)
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+
+    /// files kept in memory, for trying out loading without touching disk
+    #[derive(Default)]
+    struct MemoryStorage {
+        files: RefCell<HashMap<String, String>>,
+    }
+    impl Storage for MemoryStorage {
+        fn read(&self, name: &str) -> Result<Option<String>, String> {
+            Ok(self.files.borrow().get(name).cloned())
+        }
+
+        fn write(&self, name: &str, contents: &[u8]) -> Result<(), String> {
+            let contents = String::from_utf8(contents.to_vec()).map_err(|err| err.to_string())?;
+            self.files.borrow_mut().insert(name.to_string(), contents);
+            Ok(())
+        }
+
+        fn set_aside(&self, name: &str) -> Result<PathBuf, String> {
+            let backup = format!("{}.bak", name);
+            let contents = self.files.borrow_mut().remove(name).ok_or("no file to back up")?;
+            self.files.borrow_mut().insert(backup.clone(), contents);
+            Ok(self.path(&backup))
+        }
+
+        fn path(&self, name: &str) -> PathBuf {
+            PathBuf::from(name)
+        }
+    }
+
+    #[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+    struct Tally {
+        count: u32,
+    }
+
+    fn load(storage: &MemoryStorage) -> (Tally, Option<String>) {
+        load_versioned(storage, "tally.json", &[], "the tally").unwrap()
+    }
+
+    #[test]
+    fn current_files_load_without_a_notice() {
+        let storage = MemoryStorage::default();
+        storage.write("tally.json", br#"{ "version": 0, "count": 4 }"#).unwrap();
+        assert_eq!(load(&storage), (Tally { count: 4 }, None));
+    }
+
+    #[test]
+    fn files_from_newer_versions_are_backed_up_and_reset() {
+        let storage = MemoryStorage::default();
+        let newer = r#"{ "version": 3, "count": 4 }"#;
+        storage.write("tally.json", newer.as_bytes()).unwrap();
+
+        let (tally, notice) = load(&storage);
+        assert_eq!(tally, Tally::default());
+        assert!(notice.unwrap().starts_with("The tally were saved by a newer version"));
+        assert_eq!(storage.read("tally.json.bak").unwrap().as_deref(), Some(newer));
+        assert_eq!(storage.read("tally.json").unwrap(), None);
+    }
+
+    #[test]
+    fn broken_files_are_backed_up_and_reset() {
+        let storage = MemoryStorage::default();
+        storage.write("tally.json", b"{ not json").unwrap();
+
+        let (tally, notice) = load(&storage);
+        assert_eq!(tally, Tally::default());
+        assert!(notice.unwrap().starts_with("Couldn't read the tally"));
+        assert!(storage.read("tally.json.bak").unwrap().is_some());
+    }
+}
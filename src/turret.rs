@@ -0,0 +1,57 @@
+//! gun turrets mounted on some large asteroids, shooting at the ship
+
+use crate::angle::Angle;
+use crate::bullet::{Bullet, Projectile};
+use crate::physics::{Point, TimeScale};
+use crate::random::Rng;
+use serde::{Deserialize, Serialize};
+
+/// chance for a new large asteroid to carry a turret
+pub const TURRET_CHANCE: f32 = 0.12;
+pub const TURRET_SIZE: f32 = 5.;
+/// degrees per second the turret can swing its barrel
+const TURRET_TURN_RATE: f32 = 45.;
+const TURRET_FIRE_INTERVAL: f64 = 2.5;
+/// how close to the ship the barrel has to point before it fires, in
+/// degrees either way
+const TURRET_AIM_TOLERANCE: f32 = 10.;
+
+/// a turret riding at the middle of its asteroid, slowly swinging its
+/// barrel around to follow the ship and firing whenever it's lined up. It
+/// goes when the asteroid is destroyed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Turret {
+    /// the way the barrel points
+    pub aim: Angle,
+    pub last_shot: f64,
+}
+impl Turret {
+    pub fn new(rng: &mut Rng) -> Turret {
+        Turret {
+            aim: rng.angle(),
+            last_shot: 0.,
+        }
+    }
+
+    /// swings the barrel toward `target` over `delta` seconds, from the
+    /// asteroid at `mount`, and returns a shot if it's lined up and loaded
+    pub fn update(
+        &mut self,
+        mount: Point,
+        time_scale: TimeScale,
+        target: Point,
+        delta: f32,
+        now: f64,
+    ) -> Option<Bullet> {
+        let max_turn = TURRET_TURN_RATE * time_scale.apply(delta);
+        let turn = self.aim.shortest_difference(Angle::towards(mount, target));
+        self.aim.rotate(turn.clamp(-max_turn, max_turn));
+
+        let loaded = (now - self.last_shot) * time_scale.0 as f64 >= TURRET_FIRE_INTERVAL;
+        if !loaded || turn.abs() > TURRET_AIM_TOLERANCE {
+            return None;
+        }
+        self.last_shot = now;
+        Some(Bullet::aimed(mount, self.aim, Projectile::TurretShot, time_scale, now))
+    }
+}
@@ -0,0 +1,77 @@
+//! the ship's guns, and the patterns they fire in
+
+use crate::bullet::{Bullet, TIME_BETWEEN_SHOTS};
+use crate::ship::Ship;
+use serde::{Deserialize, Serialize};
+
+/// degrees between neighboring bullets of a spread shot
+const SPREAD_ANGLE: f32 = 12.;
+/// seconds between spread shots
+const SPREAD_COOLDOWN: f64 = 0.3;
+/// seconds between missiles
+const MISSILE_COOLDOWN: f64 = 0.6;
+
+/// a gun the ship can carry
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Weapon {
+    /// one bullet straight ahead, with no limit on shots
+    #[default]
+    #[serde(alias = "Single")]
+    Blaster,
+    /// three bullets in a small fan
+    Spread,
+    /// a missile that steers itself toward the nearest asteroid
+    Missiles,
+    /// a beam that burns the first asteroid in its way for as long as the
+    /// trigger is held, until it overheats
+    Laser,
+}
+impl Weapon {
+    /// every weapon, in the order the number keys pick them and cycling
+    /// goes through them
+    pub const ALL: [Weapon; 4] = [Weapon::Blaster, Weapon::Spread, Weapon::Missiles, Weapon::Laser];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Weapon::Blaster => "Blaster",
+            Weapon::Spread => "Spread",
+            Weapon::Missiles => "Missiles",
+            Weapon::Laser => "Laser",
+        }
+    }
+
+    /// seconds between shots. The laser has none: it burns for as long as
+    /// it's on, and its heat is what holds it back.
+    pub fn cooldown(&self) -> f64 {
+        match self {
+            Weapon::Blaster => TIME_BETWEEN_SHOTS,
+            Weapon::Spread => SPREAD_COOLDOWN,
+            Weapon::Missiles => MISSILE_COOLDOWN,
+            Weapon::Laser => 0.,
+        }
+    }
+
+    /// whether each shot uses up ammo, which has to be picked up
+    pub fn uses_ammo(&self) -> bool {
+        matches!(self, Weapon::Missiles)
+    }
+
+    /// the bullets one pull of the trigger sends out of the ship's nose
+    pub fn fire(&self, ship: &Ship, now: f64) -> Vec<Bullet> {
+        let offsets: &[f32] = match self {
+            Weapon::Blaster => &[0.],
+            Weapon::Spread => &[-SPREAD_ANGLE, 0., SPREAD_ANGLE],
+            Weapon::Missiles => return vec![Bullet::missile(ship, now)],
+            // the beam is worked out with the collisions instead
+            Weapon::Laser => return Vec::new(),
+        };
+        offsets
+            .iter()
+            .map(|offset| {
+                let mut aim = ship.rotation;
+                aim.rotate(*offset);
+                Bullet::fire(ship, aim, now)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,51 @@
+//! a list of entries picked with the arrow keys, for menus
+
+use crate::render::draw_centered_text;
+use macroquad::prelude::*;
+
+/// what the player did to the highlighted entry
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Choice {
+    /// left, for the entry's previous value
+    Previous,
+    /// right or enter, for the entry's next value or to pick it
+    Next,
+}
+
+/// which entry of a list menu is highlighted
+#[derive(Default)]
+pub struct OptionList {
+    pub selected: usize,
+}
+impl OptionList {
+    /// moves the highlight with up and down, wrapping around a list of
+    /// `len` entries, and says what was done to the highlighted one
+    pub fn update(&mut self, len: usize) -> Option<Choice> {
+        if is_key_pressed(KeyCode::Down) {
+            self.selected = (self.selected + 1) % len;
+        } else if is_key_pressed(KeyCode::Up) {
+            self.selected = (self.selected + len - 1) % len;
+        }
+
+        if is_key_pressed(KeyCode::Left) {
+            Some(Choice::Previous)
+        } else if is_key_pressed(KeyCode::Right) || is_key_pressed(KeyCode::Enter) {
+            Some(Choice::Next)
+        } else {
+            None
+        }
+    }
+
+    /// draws `entries` centered down the screen from `top`, with the
+    /// highlighted one marked
+    pub fn draw(&self, entries: &[String], top: f32) {
+        for (line, entry) in entries.iter().enumerate() {
+            let text = if line == self.selected {
+                format!("> {} <", entry)
+            } else {
+                entry.clone()
+            };
+            draw_centered_text(&text, top + line as f32 * 30., 24.);
+        }
+    }
+}
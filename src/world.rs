@@ -0,0 +1,674 @@
+//! everything that makes up a game in progress, and saved copies of it
+
+use crate::asteroid::{Asteroid, SpawnWarning};
+use crate::bullet::{Bullet, Projectile};
+use crate::events::GameEvent;
+use crate::input::{InputFrame, WeaponChoice};
+use crate::migrate::{self, SAVE_MIGRATIONS};
+use crate::physics::{
+    bounce_apart, bounce_off_walls, field_size, in_arena, interpolate, wrap_around, Point, TICK,
+};
+use crate::powerup::{PowerUp, POWERUP_LIFETIME, POWERUP_SIZE};
+use crate::random::Rng;
+use crate::satellite::{Satellite, SATELLITE_LIFETIME, SATELLITE_SIZE};
+use crate::saucer::{Saucer, SaucerKind, SaucerWarning, SAUCER_DELAY, SAUCER_INTERVAL};
+use crate::scoring;
+use crate::settings::{Density, Difficulty, Edges};
+use crate::ship::{Ship, SHIP_HEIGHT, STARTING_LIVES};
+use crate::spawn::SpawnPattern;
+use crate::storage::Storage;
+use crate::weapon::Weapon;
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// asteroids added to each wave after the first
+const EXTRA_ASTEROIDS_PER_WAVE: usize = 2;
+/// how much faster asteroids move with each wave after the first
+const SPEEDUP_PER_WAVE: f32 = 0.15;
+/// seconds the "Wave N" banner stays up
+pub const WAVE_BANNER_TIME: f64 = 2.;
+/// seconds of par time each of a wave's asteroids adds
+const PAR_SECS_PER_ASTEROID: f64 = 10.;
+/// simulation steps between the points of a predicted path
+const PATH_STEPS_PER_POINT: usize = 6;
+/// chance for a hyperspace jump to come out inside an asteroid
+const HYPERSPACE_MISHAP_CHANCE: f32 = 0.1;
+
+/// everything that makes up a game in progress, apart from effects that are
+/// only there to look at
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct World {
+    pub ship: Ship,
+    pub asteroids: Vec<Asteroid>,
+    pub bullets: Vec<Bullet>,
+    pub spawn_warnings: Vec<SpawnWarning>,
+    /// satellites drifting free after their asteroid was destroyed
+    pub satellites: Vec<Satellite>,
+    #[serde(default)]
+    pub powerups: Vec<PowerUp>,
+    #[serde(default)]
+    pub saucer: Option<Saucer>,
+    /// the next saucer, while it's being warned of
+    #[serde(default)]
+    pub saucer_warning: Option<SaucerWarning>,
+    /// shots fired by saucers and turrets, which only the ship has to worry
+    /// about
+    #[serde(default, alias = "saucer_shots")]
+    pub enemy_shots: Vec<Bullet>,
+    /// when the next saucer arrives, if there isn't one already
+    #[serde(default)]
+    pub next_saucer: f64,
+    /// ships left, including the one in play
+    #[serde(default = "starting_lives")]
+    pub lives: u8,
+    #[serde(default = "first_wave")]
+    pub wave: u32,
+    /// when the current wave's asteroids were announced
+    #[serde(default)]
+    pub wave_start: f64,
+    /// how quickly the current wave should be cleared, in seconds, to earn
+    /// a speed bonus
+    #[serde(default)]
+    pub wave_par: f64,
+    pub last_shot: f64,
+    pub run_start: f64,
+    /// whether a cheat code was used this run, which keeps it off the high
+    /// score table
+    #[serde(default)]
+    pub cheated: bool,
+    /// the run's score so far, saved with the world so a restored snapshot
+    /// sends the same saucers. The run report reads it from here.
+    #[serde(default)]
+    pub score: u32,
+    #[serde(default)]
+    pub difficulty: Difficulty,
+    /// what `rng` was seeded with when the run began
+    #[serde(default)]
+    pub seed: u64,
+    /// the generator for everything random in the world, saved with it so a
+    /// snapshot plays out the same every time it's restored
+    #[serde(default)]
+    pub rng: Rng,
+    /// where a smart bomb went off this step, for the collisions to blow
+    /// apart what's near it
+    #[serde(skip)]
+    pub bomb_blast: Option<Point>,
+    /// the simulation clock, in seconds. It only moves forward as the world
+    /// is stepped, so it stops while the game is paused.
+    #[serde(default)]
+    pub time: f64,
+}
+impl World {
+    /// a fresh game with the ship in the center and the opening asteroids on
+    /// their way in
+    pub fn new(density: Density, difficulty: Difficulty, seed: u64) -> World {
+        let (width, height) = field_size();
+        let center = Point {
+            x: width / 2.,
+            y: height / 2.,
+        };
+        let ship = Ship {
+            pos: center,
+            last_pos: center,
+            ..Ship::default()
+        };
+
+        let mut world = World {
+            ship,
+            asteroids: Vec::new(),
+            bullets: Vec::new(),
+            spawn_warnings: Vec::new(),
+            satellites: Vec::new(),
+            powerups: Vec::new(),
+            saucer: None,
+            saucer_warning: None,
+            enemy_shots: Vec::new(),
+            next_saucer: 0.,
+            lives: difficulty.starting_lives(),
+            wave: 1,
+            wave_start: 0.,
+            wave_par: 0.,
+            last_shot: 0.,
+            run_start: 0.,
+            cheated: false,
+            score: 0,
+            difficulty,
+            seed,
+            rng: Rng::new(seed),
+            bomb_blast: None,
+            time: 0.,
+        };
+        world.warn_of_wave(density);
+        world
+    }
+
+    /// moves on to the next wave once the field is cleared, with a smart
+    /// bomb for clearing it
+    pub fn next_wave(&mut self, density: Density) {
+        self.wave += 1;
+        self.ship.add_bomb();
+        self.warn_of_wave(density);
+    }
+
+    /// puts up warnings for the current wave's asteroids, in the wave's
+    /// formation. Each wave after the first brings a few more of them, and
+    /// they move faster.
+    fn warn_of_wave(&mut self, density: Density) {
+        let later_waves = self.wave.saturating_sub(1);
+        let (width, height) = field_size();
+        let count = density.asteroid_count(width, height) + later_waves as usize * EXTRA_ASTEROIDS_PER_WAVE;
+        let speed = (1. + later_waves as f32 * SPEEDUP_PER_WAVE) * self.difficulty.asteroid_speed();
+
+        let pattern = SpawnPattern::for_wave(self.wave, &mut self.rng);
+        for mut asteroid in pattern.generate(count, self.ship.pos, self.wave, &mut self.rng) {
+            asteroid.vel.x *= speed;
+            asteroid.vel.y *= speed;
+            self.spawn_warnings.push(SpawnWarning::new(asteroid, self.time));
+        }
+        self.wave_start = self.time;
+        self.wave_par = count as f64 * PAR_SECS_PER_ASTEROID;
+        self.next_saucer = self.time + SAUCER_DELAY;
+    }
+
+    /// steps the simulation: steers and fires the ship by the controls, then
+    /// moves everything on by `delta` seconds and places any asteroids whose
+    /// warning has run out. Asteroids pass through each other unless
+    /// `asteroids_bounce`.
+    pub fn update(
+        &mut self,
+        input: &InputFrame,
+        edges: Edges,
+        asteroids_bounce: bool,
+        delta: f32,
+        events: &mut Vec<GameEvent>,
+    ) {
+        self.remember_positions();
+        self.time += delta as f64;
+        let now = self.time;
+
+        self.ship.steer(input, delta);
+        self.ship.recharge(delta);
+
+        if input.bomb && self.ship.can_bomb(now) {
+            self.ship.drop_bomb(now);
+            self.bomb_blast = Some(self.ship.pos);
+        }
+        if input.hyperspace && self.ship.can_jump(now) {
+            let exit = self.hyperspace_exit();
+            self.ship.jump(exit, now);
+        }
+
+        match input.weapon {
+            Some(WeaponChoice::Next) => self.ship.cycle_weapon(now),
+            Some(WeaponChoice::Pick(weapon)) => self.ship.select_weapon(weapon, now),
+            None => {}
+        }
+        self.ship.put_away_spent_weapon(now);
+
+        let laser = self.ship.weapon == Weapon::Laser;
+        self.ship.update_laser(input.fire && laser, delta);
+        if input.fire && !laser && self.ship.can_fire(self.last_shot, now) {
+            for bullet in self.ship.weapon.fire(&self.ship, now) {
+                self.bullets.push(bullet);
+                events.push(GameEvent::ShotFired);
+            }
+            self.ship.spend_ammo(now);
+
+            self.last_shot = now;
+        }
+
+        // move ship forward
+        move_ship(&mut self.ship, edges, delta);
+
+        for bullet in self.bullets.iter_mut() {
+            if bullet.projectile == Projectile::Missile {
+                let nearest = self.asteroids.iter().min_by(|a, b| {
+                    a.pos.distance(&bullet.pos).total_cmp(&b.pos.distance(&bullet.pos))
+                });
+                if let Some(target) = nearest {
+                    bullet.home_in(target.pos, delta);
+                }
+            }
+            bullet.advance(delta);
+            match edges {
+                Edges::Wrap => wrap_around(&mut bullet.pos),
+                // bullets stop at the arena walls
+                Edges::Walls => bullet.collided |= !in_arena(&bullet.pos),
+            }
+        }
+        for asteroid in self.asteroids.iter_mut() {
+            move_asteroid(asteroid, edges, delta);
+            let center = asteroid.pos;
+            for satellite in asteroid.satellites.iter_mut() {
+                satellite.orbit(center, delta);
+            }
+            if let Some(turret) = asteroid.turret.as_mut() {
+                if let Some(shot) = turret.update(center, asteroid.time_scale, self.ship.pos, delta, now) {
+                    self.enemy_shots.push(shot);
+                }
+            }
+        }
+        if asteroids_bounce {
+            bounce_asteroids(&mut self.asteroids);
+        }
+        for powerup in self.powerups.iter_mut() {
+            powerup.advance(delta);
+            match edges {
+                Edges::Wrap => wrap_around(&mut powerup.pos),
+                Edges::Walls => bounce_off_walls(&mut powerup.pos, &mut powerup.vel, POWERUP_SIZE),
+            }
+        }
+        for satellite in self.satellites.iter_mut() {
+            satellite.advance(delta);
+            match edges {
+                Edges::Wrap => wrap_around(&mut satellite.pos),
+                Edges::Walls => bounce_off_walls(&mut satellite.pos, &mut satellite.vel, SATELLITE_SIZE),
+            }
+        }
+
+        self.update_saucer(edges, delta, events);
+
+        for warning in self.spawn_warnings.iter_mut() {
+            let delta = warning.asteroid.time_scale.apply(delta);
+            warning.pulse.update(delta);
+            warning.blink.update(delta);
+        }
+
+        // place any warned asteroids whose warning has run out
+        let mut index = 0;
+        while index < self.spawn_warnings.len() {
+            if self.spawn_warnings[index].spawn_time <= now {
+                self.asteroids.push(self.spawn_warnings.remove(index).asteroid);
+            } else {
+                index += 1;
+            }
+        }
+    }
+
+    /// somewhere for a hyperspace jump to come out, clear of the asteroids,
+    /// except on the odd jump that lands right inside one
+    fn hyperspace_exit(&mut self) -> Point {
+        if !self.asteroids.is_empty() && self.rng.chance(HYPERSPACE_MISHAP_CHANCE) {
+            let index = self.rng.range(0., self.asteroids.len() as f32) as usize;
+            return self.asteroids[index.min(self.asteroids.len() - 1)].pos;
+        }
+        let avoid: Vec<(Point, f32)> = self
+            .asteroids
+            .iter()
+            .map(|asteroid| (asteroid.pos, asteroid.size + SHIP_HEIGHT))
+            .collect();
+        self.rng.point_clear_of(&avoid)
+    }
+
+    /// warns of a saucer once one is due and brings it on when the warning
+    /// runs out, flies it across and has it shoot at the ship, and moves all
+    /// the enemy shots along
+    fn update_saucer(&mut self, edges: Edges, delta: f32, events: &mut Vec<GameEvent>) {
+        let now = self.time;
+        if self.saucer.is_none() && self.saucer_warning.is_none() && now >= self.next_saucer {
+            let kind = SaucerKind::roll(self.score, &mut self.rng);
+            self.saucer_warning = Some(SaucerWarning::new(kind, now, &mut self.rng));
+        }
+        if let Some(warning) = self.saucer_warning.as_mut() {
+            warning.blink.update(delta);
+        }
+        if self.saucer_warning.as_ref().is_some_and(|warning| warning.arrival <= now) {
+            self.saucer = self.saucer_warning.take().map(|warning| warning.saucer);
+            events.push(GameEvent::SaucerArrived);
+        }
+        if let Some(saucer) = self.saucer.as_mut() {
+            saucer.advance(delta, edges, now, &mut self.rng);
+            if let Some(shot) = saucer.fire(self.ship.pos, self.ship.vel, now, &mut self.rng) {
+                self.enemy_shots.push(shot);
+            }
+            if saucer.has_crossed() {
+                self.saucer = None;
+                self.next_saucer = now + SAUCER_INTERVAL;
+            }
+        }
+
+        for shot in self.enemy_shots.iter_mut() {
+            shot.advance(delta);
+            match edges {
+                Edges::Wrap => wrap_around(&mut shot.pos),
+                Edges::Walls => shot.collided |= !in_arena(&shot.pos),
+            }
+        }
+    }
+
+    /// drops everything that was destroyed, collected or has run out of time
+    pub fn remove_spent(&mut self) {
+        let now = self.time;
+        self.satellites.retain(|satellite| {
+            satellite.released_time + SATELLITE_LIFETIME > now && !satellite.collected
+        });
+        self.bullets
+            .retain(|bullet| bullet.initial_frame + bullet.projectile.lifetime() > now && !bullet.collided);
+        self.asteroids.retain(|asteroid| !asteroid.collided);
+        self.powerups
+            .retain(|powerup| powerup.spawn_time + POWERUP_LIFETIME > now && !powerup.collected);
+        self.enemy_shots
+            .retain(|shot| shot.initial_frame + shot.projectile.lifetime() > now && !shot.collided);
+        if self.saucer.as_ref().is_some_and(|saucer| saucer.collided) {
+            self.saucer = None;
+            self.next_saucer = now + SAUCER_INTERVAL;
+        }
+    }
+
+    /// adds what each of this step's events is worth to the score
+    pub fn add_points(&mut self, events: &[GameEvent]) {
+        self.score += events.iter().map(scoring::points).sum::<u32>();
+    }
+
+    /// where each asteroid will be over the next `secs` seconds, stepped
+    /// forward the same way `update` moves them, as a point every few steps
+    pub fn predicted_paths(&self, edges: Edges, secs: f32) -> Vec<Vec<Point>> {
+        let steps = (secs / TICK) as usize;
+        self.asteroids
+            .iter()
+            .map(|asteroid| {
+                let mut asteroid = asteroid.clone();
+                (1..=steps)
+                    .filter_map(|step| {
+                        move_asteroid(&mut asteroid, edges, TICK);
+                        (step % PATH_STEPS_PER_POINT == 0).then_some(asteroid.pos)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// where the ship will drift over the next `secs` seconds if it coasts,
+    /// and where it will go if it thrusts the whole time, stepped forward
+    /// the same way `update` moves it
+    pub fn predicted_ship_paths(&self, edges: Edges, secs: f32) -> (Vec<Point>, Vec<Point>) {
+        let steps = (secs / TICK) as usize;
+        let path = |thrust: bool| {
+            let mut ship = self.ship.clone();
+            let input = InputFrame {
+                thrust,
+                ..InputFrame::default()
+            };
+            (1..=steps)
+                .filter_map(|step| {
+                    ship.steer(&input, TICK);
+                    move_ship(&mut ship, edges, TICK);
+                    (step % PATH_STEPS_PER_POINT == 0).then_some(ship.pos)
+                })
+                .collect()
+        };
+        (path(false), path(true))
+    }
+
+    /// takes a life after the ship is hit, unless this is a `practice` run.
+    /// If any are left, the ship respawns in the center; returns whether it
+    /// did.
+    pub fn lose_life(&mut self, practice: bool) -> bool {
+        if !practice {
+            self.lives = self.lives.saturating_sub(1);
+        }
+        if self.lives == 0 {
+            return false;
+        }
+
+        let (width, height) = field_size();
+        let center = Point {
+            x: width / 2.,
+            y: height / 2.,
+        };
+        self.ship.respawn(center, self.time);
+        true
+    }
+
+    /// notes where everything is before it moves, so drawing can blend
+    /// between this step and the next
+    fn remember_positions(&mut self) {
+        self.ship.last_pos = self.ship.pos;
+        self.ship.last_rotation = self.ship.rotation;
+        for bullet in self.bullets.iter_mut() {
+            bullet.last_pos = bullet.pos;
+        }
+        for asteroid in self.asteroids.iter_mut() {
+            asteroid.last_pos = asteroid.pos;
+            asteroid.last_rotation = asteroid.rotation;
+            for satellite in asteroid.satellites.iter_mut() {
+                satellite.last_pos = satellite.pos;
+            }
+        }
+        for satellite in self.satellites.iter_mut() {
+            satellite.last_pos = satellite.pos;
+        }
+        for powerup in self.powerups.iter_mut() {
+            powerup.last_pos = powerup.pos;
+        }
+        if let Some(saucer) = self.saucer.as_mut() {
+            saucer.last_pos = saucer.pos;
+        }
+        for shot in self.enemy_shots.iter_mut() {
+            shot.last_pos = shot.pos;
+        }
+    }
+
+    /// a copy of the world for drawing, with everything `amount` of the way
+    /// from where it was at the start of the last step to where it is now
+    pub fn interpolated(&self, amount: f32) -> World {
+        let mut world = self.clone();
+        world.ship.pos = interpolate(self.ship.last_pos, self.ship.pos, amount);
+        world.ship.rotation = self.ship.last_rotation.lerp(self.ship.rotation, amount);
+        for bullet in world.bullets.iter_mut() {
+            bullet.pos = interpolate(bullet.last_pos, bullet.pos, amount);
+        }
+        for asteroid in world.asteroids.iter_mut() {
+            asteroid.pos = interpolate(asteroid.last_pos, asteroid.pos, amount);
+            asteroid.rotation = asteroid.last_rotation.lerp(asteroid.rotation, amount);
+            for satellite in asteroid.satellites.iter_mut() {
+                satellite.pos = interpolate(satellite.last_pos, satellite.pos, amount);
+            }
+        }
+        for satellite in world.satellites.iter_mut() {
+            satellite.pos = interpolate(satellite.last_pos, satellite.pos, amount);
+        }
+        for powerup in world.powerups.iter_mut() {
+            powerup.pos = interpolate(powerup.last_pos, powerup.pos, amount);
+        }
+        if let Some(saucer) = world.saucer.as_mut() {
+            saucer.pos = interpolate(saucer.last_pos, saucer.pos, amount);
+        }
+        for shot in world.enemy_shots.iter_mut() {
+            shot.pos = interpolate(shot.last_pos, shot.pos, amount);
+        }
+        world
+    }
+
+    /// whether every asteroid, including those still on their way in, is gone
+    pub fn is_cleared(&self) -> bool {
+        self.asteroids.is_empty() && self.spawn_warnings.is_empty()
+    }
+}
+
+/// moves the ship on by `delta` seconds, keeping it in play by the rules for
+/// the screen's edges
+fn move_ship(ship: &mut Ship, edges: Edges, delta: f32) {
+    ship.advance(delta);
+    match edges {
+        Edges::Wrap => wrap_around(&mut ship.pos),
+        Edges::Walls => bounce_off_walls(&mut ship.pos, &mut ship.vel, SHIP_HEIGHT / 2.),
+    }
+}
+
+/// moves an asteroid on by `delta` seconds, keeping it in play by the rules
+/// for the screen's edges
+fn move_asteroid(asteroid: &mut Asteroid, edges: Edges, delta: f32) {
+    asteroid.advance(delta);
+    match edges {
+        Edges::Wrap => wrap_around(&mut asteroid.pos),
+        Edges::Walls => bounce_off_walls(&mut asteroid.pos, &mut asteroid.vel, asteroid.size),
+    }
+}
+
+/// bounces every pair of overlapping asteroids off each other
+fn bounce_asteroids(asteroids: &mut [Asteroid]) {
+    for first in 0..asteroids.len() {
+        let (before, after) = asteroids.split_at_mut(first + 1);
+        let a = &mut before[first];
+        for b in after.iter_mut() {
+            bounce_apart(&mut a.pos, &mut a.vel, a.size, &mut b.pos, &mut b.vel, b.size);
+        }
+    }
+}
+
+fn starting_lives() -> u8 {
+    STARTING_LIVES
+}
+
+fn first_wave() -> u32 {
+    1
+}
+
+/// a saved copy of a game in progress. Snapshots are plain serde data, so
+/// they can be written to disk as save games or kept in memory. The world's
+/// random number generator is saved with it, so the game plays out the same
+/// from a snapshot every time.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// when the snapshot was taken, by the world's clock
+    pub time: f64,
+    pub world: World,
+}
+impl Snapshot {
+    pub fn capture(world: &World) -> Snapshot {
+        Snapshot {
+            time: world.time,
+            world: world.clone(),
+        }
+    }
+
+    pub fn restore(self) -> World {
+        let mut world = self.world;
+        world.remember_positions();
+        world
+    }
+
+    /// the snapshot as a save file, marked with the current save version
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        let mut save = serde_json::to_value(self)?;
+        migrate::stamp(&mut save, SAVE_MIGRATIONS);
+        Ok(save.to_string())
+    }
+
+    pub fn save(&self, storage: &dyn Storage, name: &str) -> Result<(), String> {
+        let json = self.to_json().map_err(|err| err.to_string())?;
+        storage.write(name, json.as_bytes())
+    }
+
+    /// reads a save back, upgrading it on disk if it's from an older version
+    /// of the game. One that isn't valid is moved aside, so it can still be
+    /// looked at but isn't tried again.
+    pub fn load(storage: &dyn Storage, name: &str) -> Result<Snapshot, String> {
+        let json = storage
+            .read(name)?
+            .ok_or_else(|| format!("There's no save at {}", storage.path(name).display()))?;
+        let set_aside = |err: serde_json::Error| match storage.set_aside(name) {
+            Ok(backup) => format!("{} is not a valid save, so it was moved to {}", name, backup.display()),
+            Err(backup_err) => format!("{} is not a valid save: {}. {}", name, err, backup_err),
+        };
+
+        let mut save = serde_json::from_str(&json).map_err(set_aside)?;
+        let upgraded = migrate::upgrade(&mut save, SAVE_MIGRATIONS)
+            .map_err(|err| format!("Couldn't load {}: {}", name, err))?;
+        let snapshot: Snapshot = serde_json::from_value(save).map_err(set_aside)?;
+        if upgraded {
+            // if this fails the save is still good, and is upgraded again
+            // next time
+            let _ = snapshot.save(storage, name);
+        }
+        Ok(snapshot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collision::check_collisions;
+    use crate::effects::Effects;
+    use crate::saucer::SAUCER_WARNING_TIME;
+
+    /// steps `world` on by `ticks` with the ship spinning and firing, the way
+    /// the game does between frames
+    fn play(world: &mut World, ticks: usize) {
+        let input = InputFrame {
+            right: true,
+            fire: true,
+            ..InputFrame::default()
+        };
+        let mut effects = Effects::default();
+        let mut events = Vec::new();
+        for _ in 0..ticks {
+            world.update(&input, Edges::Wrap, true, TICK, &mut events);
+            let now = world.time;
+            if check_collisions(world, &mut effects, &mut events, false, now) {
+                world.lose_life(false);
+            }
+            world.remove_spent();
+            world.add_points(&events);
+            events.clear();
+        }
+    }
+
+    #[test]
+    fn restored_snapshots_play_out_the_same() {
+        let mut world = World::new(Density::Dense, Difficulty::Normal, 1);
+        play(&mut world, 240);
+        let save = Snapshot::capture(&world).to_json().unwrap();
+        play(&mut world, 1200);
+
+        let mut restored = serde_json::from_str::<Snapshot>(&save).unwrap().restore();
+        play(&mut restored, 1200);
+        assert_eq!(serde_json::to_value(&restored).unwrap(), serde_json::to_value(&world).unwrap());
+    }
+
+    #[test]
+    fn restored_snapshots_keep_their_score() {
+        let mut world = World::new(Density::Dense, Difficulty::Normal, 3);
+        world.next_saucer = world.time;
+        let snapshot = Snapshot::capture(&world);
+        // enough to send nothing but small saucers
+        world.add_points(&[GameEvent::WaveCleared {
+            wave: 1,
+            secs: 0.,
+            bonus: 40_000,
+        }]);
+
+        let mut restored = snapshot.restore();
+        restored.update(&InputFrame::default(), Edges::Wrap, true, TICK, &mut Vec::new());
+        assert_eq!(restored.score, 0);
+        let kind = restored.saucer_warning.map(|warning| warning.saucer.kind);
+        assert_eq!(kind, Some(SaucerKind::Large));
+    }
+
+    #[test]
+    fn saucers_are_warned_of_before_they_fly_in() {
+        let mut world = World::new(Density::Dense, Difficulty::Normal, 4);
+        world.next_saucer = world.time;
+        let mut events = Vec::new();
+        world.update(&InputFrame::default(), Edges::Wrap, true, TICK, &mut events);
+        assert!(world.saucer_warning.is_some() && world.saucer.is_none());
+
+        for _ in 0..(SAUCER_WARNING_TIME / TICK as f64) as usize + 1 {
+            world.update(&InputFrame::default(), Edges::Wrap, true, TICK, &mut events);
+        }
+        assert!(world.saucer_warning.is_none() && world.saucer.is_some());
+        assert!(matches!(events.as_slice(), [.., GameEvent::SaucerArrived]));
+    }
+
+    #[test]
+    fn capturing_a_snapshot_leaves_the_world_alone() {
+        let mut world = World::new(Density::Dense, Difficulty::Hard, 2);
+        let mut untouched = world.clone();
+        play(&mut world, 120);
+        Snapshot::capture(&world);
+        play(&mut world, 600);
+        play(&mut untouched, 720);
+        assert_eq!(serde_json::to_value(&untouched).unwrap(), serde_json::to_value(&world).unwrap());
+    }
+}